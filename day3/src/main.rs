@@ -1,71 +1,16 @@
-use prelude::*;
-
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-enum TileType {
-    Clear,
-    Tree,
-}
-
-struct Terrain {
-    width: usize,
-    height: usize,
-    tiles: Vec<TileType>,
-}
-
-impl Terrain {
-    fn from_lines(lines: Vec<String>) -> Self {
-        let width = match lines.get(0) {
-            Some(x) => x.len(),
-            None => 0,
-        };
-        let height = lines.len();
-        let mut tiles: Vec<TileType> = vec![TileType::Clear; width * height];
-
-        for (y, line) in lines.into_iter().enumerate() {
-            for (x, tile_ch) in line.chars().enumerate() {
-                let idx = y * width + x;
-                tiles[idx] = match tile_ch {
-                    '.' => TileType::Clear,
-                    '#' => TileType::Tree,
-                    _ => panic!("Unexpected tile type: {:?}", tile_ch),
-                };
-            }
-        }
-        Self {
-            width,
-            height,
-            tiles,
-        }
-    }
-
-    fn tile(&self, x: usize, y: usize) -> TileType {
-        let x = x % self.width;
-        let idx = y * self.width + x;
-        *self.tiles.get(idx).unwrap_or(&TileType::Clear)
-    }
-
-    fn height(&self) -> usize {
-        self.height
-    }
+mod render;
+mod route;
+mod terrain;
 
-    fn path_for_slope(&self, slope_x: usize, slope_y: usize) -> Vec<TileType> {
-        let mut tiles: Vec<TileType> = Vec::new();
-        let mut x = 0;
-        let mut y = 0;
-        while y <= self.height() {
-            tiles.push(self.tile(x, y));
-            x += slope_x;
-            y += slope_y;
-        }
-        tiles
-    }
-}
+use prelude::*;
+use render::Overlay;
+use route::{parse_moves, DOWN_RIGHT};
+use terrain::{Legend, Terrain};
 
 fn part1(terrain: &Terrain) -> usize {
     terrain
-        .path_for_slope(3, 1)
-        .into_iter()
-        .filter(|&x| x == TileType::Tree)
+        .slope(3, 1)
+        .filter(|&(_, _, tile)| terrain.is_tree(tile))
         .count()
 }
 
@@ -73,21 +18,62 @@ fn part2(terrain: &Terrain) -> usize {
     let slopes = vec![(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
     slopes
         .into_iter()
-        .map(|(x, y)| {
-            terrain
-                .path_for_slope(x, y)
-                .into_iter()
-                .filter(|&x| x == TileType::Tree)
-                .count()
-        })
-        .fold(1, |state, next| state * next)
+        .map(|(x, y)| terrain.trees_on_slope(x, y))
+        .product()
+}
+
+fn parse_slope(s: &str) -> (usize, usize) {
+    let (x, y) = s.split_once(',').expect("slope must be `dx,dy`");
+    (x.trim().parse().unwrap(), y.trim().parse().unwrap())
 }
 
 fn main() {
-    let terrain = Terrain::from_lines(read_input_lines("input.txt"));
+    let args: Vec<String> = std::env::args().collect();
+    let legend = match arg_value(&args, "--legend") {
+        Some(path) => Legend::parse(&read_input(path)).unwrap(),
+        None => Legend::new(),
+    };
+    let terrain = Terrain::from_lines(read_input_lines("input.txt"), &legend).unwrap();
+    let image_path = arg_value(&args, "--image");
+    let scale: usize = arg_value(&args, "--scale").map_or(4, |s| s.parse().unwrap());
+
+    let overlay = if args.iter().any(|a| a == "--route") {
+        let moves = match arg_value(&args, "--moves") {
+            Some(moves) => parse_moves(moves).unwrap(),
+            None => DOWN_RIGHT.to_vec(),
+        };
+        match terrain.shortest_route(&moves) {
+            Some(route) => {
+                println!("Route trees: {}", route.trees);
+                println!("Route cost: {}", route.cost);
+                Some(Overlay::new(&terrain, &route.cells))
+            }
+            None => {
+                println!("No route found");
+                None
+            }
+        }
+    } else if args.iter().any(|a| a == "--render") || image_path.is_some() {
+        let (dx, dy) = arg_value(&args, "--slope").map_or((3, 1), parse_slope);
+        let cells: Vec<(usize, usize)> = terrain.slope(dx, dy).map(|(x, y, _)| (x, y)).collect();
+        Some(Overlay::new(&terrain, &cells))
+    } else {
+        None
+    };
+    if let Some(overlay) = overlay {
+        match image_path {
+            Some(path) => {
+                overlay.write_image(path, scale).unwrap();
+                println!("Wrote {}", path);
+            }
+            None => print!("{}", overlay.to_text()),
+        }
+        println!("=======================");
+    }
 
     println!("Part 1");
     println!("Answer: {}", part1(&terrain));
+    println!("Path cost: {}", terrain.slope_cost(3, 1));
     println!("=======================");
     println!("Part 2");
     println!("Answer: {}", part2(&terrain));
@@ -95,7 +81,9 @@ fn main() {
 
 #[cfg(test)]
 mod tests {
-    use super::{part1, part2, Terrain};
+    use super::route::parse_moves;
+    use super::terrain::TileType;
+    use super::{part1, part2, Legend, Overlay, Terrain, DOWN_RIGHT};
 
     fn get_terrain() -> Terrain {
         let input: Vec<String> = "..##.......
@@ -112,7 +100,7 @@ mod tests {
             .split("\n")
             .map(|x| x.to_string())
             .collect();
-        Terrain::from_lines(input)
+        Terrain::from_lines(input, &Legend::new()).unwrap()
     }
 
     #[test]
@@ -125,4 +113,116 @@ mod tests {
         let terrain = get_terrain();
         assert_eq!(336, part2(&terrain));
     }
+
+    #[test]
+    fn test_slope_iterator_matches_bitset() {
+        let terrain = get_terrain();
+        for &(dx, dy) in &[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2), (13, 3), (0, 1)] {
+            let lazy = terrain
+                .slope(dx, dy)
+                .filter(|&(_, _, tile)| terrain.is_tree(tile))
+                .count();
+            assert_eq!(lazy, terrain.trees_on_slope(dx, dy));
+        }
+        let path: Vec<(usize, usize)> = terrain
+            .slope(3, 1)
+            .map(|(x, y, _)| (x, y))
+            .take(3)
+            .collect();
+        assert_eq!(vec![(0, 0), (3, 1), (6, 2)], path);
+    }
+
+    #[test]
+    fn test_legend_and_slope_cost() {
+        let legend = Legend::parse("o clear 1\nT tree 3\n^ rock 7").unwrap();
+        let lines: Vec<String> = vec!["oT^~", "T^*o", "^oTo"]
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect();
+        let terrain = Terrain::from_lines(lines, &legend).unwrap();
+        assert_eq!(TileType::ICE, terrain.tile(3, 0));
+        assert_eq!(TileType::ROCK, terrain.tile(5, 1));
+        // (0,0) clear, (1,1) rock, (2,2) tree
+        assert_eq!(1, terrain.trees_on_slope(1, 1));
+        assert_eq!(1 + 7 + 3, terrain.slope_cost(1, 1));
+
+        let err = match Terrain::from_lines(vec!["..#".to_string()], &legend) {
+            Ok(_) => panic!("expected an unknown glyph error"),
+            Err(err) => err,
+        };
+        assert!(err.contains("line 1, column 1"), "{}", err);
+
+        // New kinds, including ones that count as trees.
+        let legend = Legend::parse("x lava 3\nB bush 2 tree").unwrap();
+        let lines: Vec<String> = vec!["x.", ".B", "#."]
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect();
+        let terrain = Terrain::from_lines(lines, &legend).unwrap();
+        assert_eq!("lava", legend.kind(terrain.tile(0, 0)).name);
+        assert!(!terrain.is_tree(terrain.tile(0, 0)));
+        assert_eq!(2, terrain.trees_on_slope(1, 1));
+        assert_eq!(3 + 2 + 10, terrain.slope_cost(1, 1));
+
+        // A glyph can only be reused once its kind has moved to another glyph.
+        let err = Legend::parse("# rock 5").err().unwrap();
+        assert!(err.contains("already used by tree"), "{}", err);
+        assert!(Legend::parse("T tree 10\n# rock 5").is_ok());
+        assert!(Legend::parse("x lava 3 hot").is_err());
+    }
+
+    #[test]
+    fn test_shortest_route() {
+        let lines: Vec<String> = vec!["..#", "#..", "##."]
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect();
+        let terrain = Terrain::from_lines(lines, &Legend::new()).unwrap();
+        let route = terrain.shortest_route(&DOWN_RIGHT).unwrap();
+        assert_eq!(0, route.trees);
+        assert_eq!(
+            vec![(0, 0), (1, 0), (1, 1), (2, 1), (2, 2)],
+            route.cells[..]
+        );
+        assert_eq!(
+            "OO#\n#OO\n##O\n",
+            Overlay::new(&terrain, &route.cells).to_text()
+        );
+
+        // Only straight down is allowed, so column 0 forces two trees.
+        let route = terrain.shortest_route(&[(0, 1)]).unwrap();
+        assert_eq!(2, route.trees);
+
+        // Stepping left wraps around to the last column.
+        let route = terrain
+            .shortest_route(&parse_moves("-1,1").unwrap())
+            .unwrap();
+        assert_eq!(vec![(0, 0), (2, 1), (1, 2)], route.cells);
+        assert_eq!(1, route.trees);
+    }
+
+    #[test]
+    fn test_slope_overlay() {
+        let terrain = get_terrain();
+        let cells: Vec<(usize, usize)> = terrain.slope(3, 1).map(|(x, y, _)| (x, y)).collect();
+        let text = Overlay::new(&terrain, &cells).to_text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(11, lines.len());
+        assert_eq!("O.##.........##.........##.......", lines[0]);
+        assert_eq!("#..O#...#..#...#...#..#...#...#..", lines[1]);
+        assert_eq!("#...##....##...##....##...#X....#", lines[9]);
+        assert_eq!(7, text.matches('X').count());
+
+        // Only tiles that count as trees are hits, whatever their glyph.
+        let legend = Legend::parse("B bush 2 tree").unwrap();
+        let lines: Vec<String> = vec!["~..", ".B.", "..^"]
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect();
+        let terrain = Terrain::from_lines(lines, &legend).unwrap();
+        assert_eq!(
+            "O..\n.X.\n..O\n",
+            Overlay::new(&terrain, &[(0, 0), (1, 1), (2, 2)]).to_text()
+        );
+    }
 }
@@ -0,0 +1,195 @@
+use crate::terrain::{Terrain, TileType};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Clone, Copy)]
+struct Cell {
+    glyph: char,
+    tile: TileType,
+    tree: bool,
+    marked: bool,
+}
+
+// The terrain repeated horizontally until every marked cell fits, with marked cells drawn
+// as `X` (tree) or `O` (anything else), as in the puzzle statement.
+pub struct Overlay {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Overlay {
+    pub fn new(terrain: &Terrain, marked: &[(usize, usize)]) -> Self {
+        let max_x = marked.iter().map(|&(x, _)| x).max().unwrap_or(0);
+        let copies = if terrain.width() == 0 {
+            0
+        } else {
+            max_x / terrain.width() + 1
+        };
+        let width = terrain.width() * copies;
+        let height = terrain.height();
+        let marked: HashSet<(usize, usize)> = marked.iter().copied().collect();
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let tile = terrain.tile(x, y);
+                let tree = terrain.is_tree(tile);
+                let marked = marked.contains(&(x, y));
+                let glyph = match (marked, tree) {
+                    (true, true) => 'X',
+                    (true, false) => 'O',
+                    (false, _) => terrain.glyph(tile),
+                };
+                cells.push(Cell {
+                    glyph,
+                    tile,
+                    tree,
+                    marked,
+                });
+            }
+        }
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.cells.chunks(self.width.max(1)) {
+            out.extend(row.iter().map(|c| c.glyph));
+            out.push('\n');
+        }
+        out
+    }
+
+    // Writes a PNG or PPM depending on the file extension, drawing each cell as a
+    // `scale` x `scale` block of pixels.
+    pub fn write_image<P: AsRef<Path>>(&self, path: P, scale: usize) -> io::Result<()> {
+        let path = path.as_ref();
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut rgb: Vec<u8> = Vec::with_capacity(width * height * 3);
+        for row in self.cells.chunks(self.width.max(1)) {
+            let mut line: Vec<u8> = Vec::with_capacity(width * 3);
+            for cell in row {
+                let colour = cell_colour(cell);
+                for _ in 0..scale {
+                    line.extend_from_slice(&colour);
+                }
+            }
+            for _ in 0..scale {
+                rgb.extend_from_slice(&line);
+            }
+        }
+
+        let mut out = BufWriter::new(File::create(path)?);
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("ppm") => write_ppm(&mut out, width, height, &rgb)?,
+            Some("png") => write_png(&mut out, width, height, &rgb)?,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unsupported image format: {}", path.display()),
+                ))
+            }
+        };
+        out.flush()
+    }
+}
+
+fn cell_colour(cell: &Cell) -> [u8; 3] {
+    match (cell.marked, cell.tile) {
+        (true, _) if cell.tree => [220, 20, 20],
+        (true, _) => [255, 200, 0],
+        (false, TileType::CLEAR) => [235, 235, 220],
+        (false, TileType::TREE) => [30, 110, 40],
+        (false, TileType::ROCK) => [120, 120, 120],
+        (false, TileType::ICE) => [170, 220, 255],
+        (false, TileType::SNOW) => [255, 255, 255],
+        // Kinds added by a legend cycle through a few extra colours.
+        (false, tile) => {
+            const EXTRA: [[u8; 3]; 4] = [
+                [160, 90, 40],
+                [150, 60, 170],
+                [230, 130, 30],
+                [60, 160, 160],
+            ];
+            EXTRA[tile.index() % EXTRA.len()]
+        }
+    }
+}
+
+fn write_ppm<W: Write>(out: &mut W, width: usize, height: usize, rgb: &[u8]) -> io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", width, height)?;
+    out.write_all(rgb)
+}
+
+// Uncompressed PNG: the zlib stream uses stored deflate blocks, which keeps the encoder
+// tiny at the cost of file size.
+fn write_png<W: Write>(out: &mut W, width: usize, height: usize, rgb: &[u8]) -> io::Result<()> {
+    let mut raw: Vec<u8> = Vec::with_capacity((width * 3 + 1) * height);
+    for row in rgb.chunks((width * 3).max(1)) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut zlib: Vec<u8> = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        zlib.push(blocks.peek().is_none() as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header: Vec<u8> = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // 8-bit RGB, default compression/filter, no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    out.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'])?;
+    write_png_chunk(out, b"IHDR", &header)?;
+    write_png_chunk(out, b"IDAT", &zlib)?;
+    write_png_chunk(out, b"IEND", &[])
+}
+
+fn write_png_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data.iter()).copied());
+    out.write_all(&crc.to_be_bytes())
+}
+
+fn crc32<I: Iterator<Item = u8>>(bytes: I) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
@@ -0,0 +1,92 @@
+use crate::terrain::Terrain;
+use prelude::stringify_err;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// A single step as (dx, dy). dx may be negative; x wraps around the map like `Terrain::tile`.
+pub type Move = (isize, usize);
+
+pub const DOWN_RIGHT: [Move; 2] = [(1, 0), (0, 1)];
+
+pub struct Route {
+    pub cells: Vec<(usize, usize)>,
+    pub trees: u64,
+    pub cost: u64,
+}
+
+// Parses a move set like `1,0;0,1;1,1`.
+pub fn parse_moves(s: &str) -> Result<Vec<Move>, String> {
+    s.split(';')
+        .map(|m| {
+            let (dx, dy) = m
+                .split_once(',')
+                .ok_or_else(|| format!("expected `dx,dy`, got {:?}", m))?;
+            let dx = dx.trim().parse::<isize>().map_err(stringify_err)?;
+            let dy = dy.trim().parse::<usize>().map_err(stringify_err)?;
+            if dx == 0 && dy == 0 {
+                return Err("move (0, 0) goes nowhere".to_string());
+            }
+            Ok((dx, dy))
+        })
+        .collect()
+}
+
+impl Terrain {
+    // Dijkstra from the top-left cell to any cell on the bottom row, minimizing the number
+    // of trees on the route and breaking ties by total traversal cost.
+    pub fn shortest_route(&self, moves: &[Move]) -> Option<Route> {
+        let width = self.width();
+        let height = self.height();
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let weight = |x: usize, y: usize| -> (u64, u64) {
+            let tile = self.tile(x, y);
+            (self.is_tree(tile) as u64, self.cost(tile) as u64)
+        };
+
+        let idx = |x: usize, y: usize| y * width + x;
+        let mut best: Vec<Option<(u64, u64)>> = vec![None; width * height];
+        let mut prev: Vec<Option<usize>> = vec![None; width * height];
+        let mut queue = BinaryHeap::new();
+
+        best[idx(0, 0)] = Some(weight(0, 0));
+        queue.push(Reverse((weight(0, 0), 0, 0)));
+
+        while let Some(Reverse((dist, x, y))) = queue.pop() {
+            if best[idx(x, y)] != Some(dist) {
+                continue;
+            }
+            if y == height - 1 {
+                let mut cells = vec![(x, y)];
+                let mut at = idx(x, y);
+                while let Some(p) = prev[at] {
+                    cells.push((p % width, p / width));
+                    at = p;
+                }
+                cells.reverse();
+                return Some(Route {
+                    cells,
+                    trees: dist.0,
+                    cost: dist.1,
+                });
+            }
+            for &(dx, dy) in moves {
+                let ny = y + dy;
+                if ny >= height {
+                    continue;
+                }
+                let nx = (x as isize + dx).rem_euclid(width as isize) as usize;
+                let (trees, cost) = weight(nx, ny);
+                let next = (dist.0 + trees, dist.1 + cost);
+                let n = idx(nx, ny);
+                if best[n].is_none_or(|b| next < b) {
+                    best[n] = Some(next);
+                    prev[n] = Some(idx(x, y));
+                    queue.push(Reverse((next, nx, ny)));
+                }
+            }
+        }
+        None
+    }
+}
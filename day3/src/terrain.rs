@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+
+// Index of a tile kind in the `Legend` it came from. The built-in kinds always come
+// first, so their indexes are fixed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TileType(usize);
+
+impl TileType {
+    pub const CLEAR: TileType = TileType(0);
+    pub const TREE: TileType = TileType(1);
+    pub const ROCK: TileType = TileType(2);
+    pub const ICE: TileType = TileType(3);
+    pub const SNOW: TileType = TileType(4);
+
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileKind {
+    pub name: String,
+    pub glyph: char,
+    pub cost: u32,
+    // Counted as a tree by the slope and route queries.
+    pub tree: bool,
+}
+
+#[derive(Clone)]
+pub struct Legend {
+    kinds: Vec<TileKind>,
+}
+
+impl Legend {
+    pub fn new() -> Self {
+        let kind = |name: &str, glyph, cost, tree| TileKind {
+            name: name.to_string(),
+            glyph,
+            cost,
+            tree,
+        };
+        Self {
+            kinds: vec![
+                kind("clear", '.', 1, false),
+                kind("tree", '#', 10, true),
+                kind("rock", '^', 25, false),
+                kind("ice", '~', 2, false),
+                kind("snow", '*', 4, false),
+            ],
+        }
+    }
+
+    // One tile kind per line: `<glyph> <name> <cost> [tree]`, e.g. `^ rock 25` or
+    // `B bush 3 tree`. Naming a known kind changes its glyph and cost, any other name
+    // adds a new kind. Kinds that aren't mentioned keep their default glyph and cost.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut legend = Self::new();
+        for (line_idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = |msg: &str| format!("legend line {}: {}: {:?}", line_idx + 1, msg, line);
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let (glyph, name, cost, tree) = match parts[..] {
+                [glyph, name, cost] => (glyph, name, cost, false),
+                [glyph, name, cost, "tree"] => (glyph, name, cost, true),
+                _ => return Err(err("expected `<glyph> <name> <cost> [tree]`")),
+            };
+            let mut glyph_chars = glyph.chars();
+            let glyph = match (glyph_chars.next(), glyph_chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(err("glyph must be a single character")),
+            };
+            let cost = cost.parse::<u32>().map_err(|_| err("invalid cost"))?;
+            legend
+                .set(name, glyph, cost, tree)
+                .map_err(|msg| err(&msg))?;
+        }
+        Ok(legend)
+    }
+
+    fn set(&mut self, name: &str, glyph: char, cost: u32, tree: bool) -> Result<(), String> {
+        if let Some(other) = self
+            .kinds
+            .iter()
+            .find(|kind| kind.glyph == glyph && kind.name != name)
+        {
+            return Err(format!(
+                "glyph {:?} is already used by {}",
+                glyph, other.name
+            ));
+        }
+        match self.kinds.iter_mut().find(|kind| kind.name == name) {
+            Some(kind) => {
+                kind.glyph = glyph;
+                kind.cost = cost;
+                kind.tree |= tree;
+            }
+            None => self.kinds.push(TileKind {
+                name: name.to_string(),
+                glyph,
+                cost,
+                tree,
+            }),
+        }
+        Ok(())
+    }
+
+    pub fn kind(&self, tile: TileType) -> &TileKind {
+        &self.kinds[tile.0]
+    }
+}
+
+impl Default for Legend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Terrain {
+    width: usize,
+    height: usize,
+    tiles: Vec<TileType>,
+    legend: Legend,
+    words_per_row: usize,
+    tree_bits: Vec<u64>,
+}
+
+impl Terrain {
+    pub fn from_lines(lines: Vec<String>, legend: &Legend) -> Result<Self, String> {
+        let width = match lines.first() {
+            Some(x) => x.chars().count(),
+            None => 0,
+        };
+        let height = lines.len();
+        let words_per_row = width.div_ceil(64);
+        let mut tiles: Vec<TileType> = vec![TileType::CLEAR; width * height];
+        let glyphs: HashMap<char, TileType> = legend
+            .kinds
+            .iter()
+            .enumerate()
+            .map(|(idx, kind)| (kind.glyph, TileType(idx)))
+            .collect();
+        let mut tree_bits: Vec<u64> = vec![0; words_per_row * height];
+
+        for (y, line) in lines.into_iter().enumerate() {
+            if line.chars().count() != width {
+                return Err(format!(
+                    "line {} has width {}, expected {}",
+                    y + 1,
+                    line.chars().count(),
+                    width
+                ));
+            }
+            for (x, tile_ch) in line.chars().enumerate() {
+                let idx = y * width + x;
+                tiles[idx] = match glyphs.get(&tile_ch) {
+                    Some(&tile) => tile,
+                    None => {
+                        return Err(format!(
+                            "unexpected tile type {:?} at line {}, column {}",
+                            tile_ch,
+                            y + 1,
+                            x + 1
+                        ))
+                    }
+                };
+                if legend.kind(tiles[idx]).tree {
+                    tree_bits[y * words_per_row + x / 64] |= 1 << (x % 64);
+                }
+            }
+        }
+        Ok(Self {
+            width,
+            height,
+            tiles,
+            legend: legend.clone(),
+            words_per_row,
+            tree_bits,
+        })
+    }
+
+    pub fn tile(&self, x: usize, y: usize) -> TileType {
+        let x = x % self.width;
+        let idx = y * self.width + x;
+        *self.tiles.get(idx).unwrap_or(&TileType::CLEAR)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cost(&self, tile: TileType) -> u32 {
+        self.legend.kind(tile).cost
+    }
+
+    pub fn glyph(&self, tile: TileType) -> char {
+        self.legend.kind(tile).glyph
+    }
+
+    pub fn is_tree(&self, tile: TileType) -> bool {
+        self.legend.kind(tile).tree
+    }
+
+    pub fn slope(&self, slope_x: usize, slope_y: usize) -> SlopePath<'_> {
+        assert!(slope_y > 0, "slope must move downwards");
+        SlopePath {
+            terrain: self,
+            slope_x,
+            slope_y,
+            x: 0,
+            y: 0,
+        }
+    }
+
+    // Walks the slope over the tree bitset, keeping x wrapped without a division per row.
+    pub fn trees_on_slope(&self, slope_x: usize, slope_y: usize) -> usize {
+        assert!(slope_y > 0, "slope must move downwards");
+        if self.width == 0 {
+            return 0;
+        }
+        let step_x = slope_x % self.width;
+        let mut count = 0;
+        let mut x = 0;
+        let mut y = 0;
+        while y < self.height {
+            let word = self.tree_bits[y * self.words_per_row + x / 64];
+            count += ((word >> (x % 64)) & 1) as usize;
+            x += step_x;
+            if x >= self.width {
+                x -= self.width;
+            }
+            y += slope_y;
+        }
+        count
+    }
+
+    pub fn slope_cost(&self, slope_x: usize, slope_y: usize) -> u64 {
+        self.slope(slope_x, slope_y)
+            .map(|(_, _, tile)| self.cost(tile) as u64)
+            .sum()
+    }
+}
+
+pub struct SlopePath<'a> {
+    terrain: &'a Terrain,
+    slope_x: usize,
+    slope_y: usize,
+    x: usize,
+    y: usize,
+}
+
+impl<'a> Iterator for SlopePath<'a> {
+    type Item = (usize, usize, TileType);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.y >= self.terrain.height() {
+            return None;
+        }
+        let item = (self.x, self.y, self.terrain.tile(self.x, self.y));
+        self.x += self.slope_x;
+        self.y += self.slope_y;
+        Some(item)
+    }
+}
@@ -0,0 +1,63 @@
+use crate::passport::{FieldError, Record};
+use crate::schema::Schema;
+use std::collections::HashMap;
+use std::fmt;
+
+// `passport` is the 1-based position of the passport in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub passport: usize,
+    pub line: usize,
+    pub error: FieldError,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "passport {} (line {}): {}",
+            self.passport, self.line, self.error
+        )
+    }
+}
+
+pub fn validate(records: &[Record], schema: &Schema) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (idx, record) in records.iter().enumerate() {
+        let errors = match schema.check_record(record) {
+            Ok(_) => continue,
+            Err(errors) => errors,
+        };
+        let mut duplicates_seen: HashMap<String, usize> = HashMap::new();
+        for error in errors {
+            let mut occurrences = record.fields.iter().filter(|f| f.key == error.field());
+            let field = match &error {
+                FieldError::Missing(_) => None,
+                FieldError::Duplicate(key) => {
+                    let n = duplicates_seen.entry(key.clone()).or_insert(0);
+                    *n += 1;
+                    occurrences.nth(*n)
+                }
+                _ => occurrences.next(),
+            };
+            diagnostics.push(Diagnostic {
+                passport: idx + 1,
+                line: field.map_or(record.line, |f| f.line),
+                error,
+            });
+        }
+    }
+    diagnostics
+}
+
+// Counts of each (field, reason) pair, most common first.
+pub fn summary(diagnostics: &[Diagnostic]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for d in diagnostics {
+        let key = format!("{}: {}", d.error.field(), d.error.reason());
+        *counts.entry(key).or_insert(0) += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    counts
+}
@@ -0,0 +1,244 @@
+use crate::passport::{Field, Record};
+use std::iter::Peekable;
+use std::str::Chars;
+
+// Tracks the line and column of the next character as it goes, so positions never need
+// a rescan of the text.
+struct Cursor<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn line(&self) -> usize {
+        self.line
+    }
+
+    fn error<T>(&self, msg: &str) -> Result<T, String> {
+        Err(format!(
+            "line {}, column {}: {}",
+            self.line, self.column, msg
+        ))
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.bump();
+            Ok(())
+        } else {
+            self.error(&format!("expected {:?}", c))
+        }
+    }
+
+    // Consumes `c` if it is the next non-whitespace character.
+    fn accept(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+// Reads a JSON array of flat objects. Numbers and booleans are kept as their literal text
+// and `null` values are treated as absent fields.
+pub fn parse_json(text: &str) -> Result<Vec<Record>, String> {
+    let mut cursor = Cursor::new(text);
+    let mut records = Vec::new();
+    cursor.expect('[')?;
+    if !cursor.accept(']') {
+        loop {
+            records.push(parse_json_object(&mut cursor)?);
+            if cursor.accept(']') {
+                break;
+            }
+            cursor.expect(',')?;
+        }
+    }
+    cursor.skip_whitespace();
+    if cursor.peek().is_some() {
+        return cursor.error("unexpected trailing input");
+    }
+    Ok(records)
+}
+
+fn parse_json_object(cursor: &mut Cursor) -> Result<Record, String> {
+    cursor.expect('{')?;
+    let mut record = Record {
+        line: cursor.line(),
+        fields: Vec::new(),
+    };
+    if cursor.accept('}') {
+        return Ok(record);
+    }
+    loop {
+        cursor.skip_whitespace();
+        let line = cursor.line();
+        let key = parse_json_string(cursor)?;
+        cursor.expect(':')?;
+        if let Some(value) = parse_json_scalar(cursor)? {
+            record.fields.push(Field { key, value, line });
+        }
+        if cursor.accept('}') {
+            return Ok(record);
+        }
+        cursor.expect(',')?;
+    }
+}
+
+fn parse_json_scalar(cursor: &mut Cursor) -> Result<Option<String>, String> {
+    cursor.skip_whitespace();
+    match cursor.peek() {
+        Some('"') => parse_json_string(cursor).map(Some),
+        Some(c) if c == '-' || c.is_ascii_alphanumeric() => {
+            let mut literal = String::new();
+            while let Some(c) = cursor.peek() {
+                if c.is_ascii_alphanumeric() || "+-.".contains(c) {
+                    literal.push(c);
+                    cursor.bump();
+                } else {
+                    break;
+                }
+            }
+            match literal.as_str() {
+                "null" => Ok(None),
+                "true" | "false" => Ok(Some(literal)),
+                _ if literal.parse::<f64>().is_ok() => Ok(Some(literal)),
+                _ => cursor.error(&format!("invalid value {:?}", literal)),
+            }
+        }
+        Some('{') | Some('[') => cursor.error("nested values are not supported"),
+        _ => cursor.error("expected a value"),
+    }
+}
+
+fn parse_json_string(cursor: &mut Cursor) -> Result<String, String> {
+    cursor.expect('"')?;
+    let mut s = String::new();
+    loop {
+        match cursor.bump() {
+            Some('"') => return Ok(s),
+            Some('\\') => {
+                let c = match cursor.bump() {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('/') => '/',
+                    Some('b') => '\u{8}',
+                    Some('f') => '\u{c}',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| cursor.bump()).collect();
+                        match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                            Some(c) => c,
+                            None => return cursor.error("invalid unicode escape"),
+                        }
+                    }
+                    _ => return cursor.error("invalid escape"),
+                };
+                s.push(c);
+            }
+            Some(c) => s.push(c),
+            None => return cursor.error("unterminated string"),
+        }
+    }
+}
+
+// Reads CSV with a header row naming the fields. Quoted cells may contain commas, newlines
+// and doubled quotes; empty cells are treated as absent fields.
+pub fn parse_csv(text: &str) -> Result<Vec<Record>, String> {
+    let mut cursor = Cursor::new(text);
+    let header = match parse_csv_row(&mut cursor)? {
+        Some((_, cells)) => cells,
+        None => return Ok(Vec::new()),
+    };
+    let mut records = Vec::new();
+    while let Some((line, cells)) = parse_csv_row(&mut cursor)? {
+        if cells.len() == 1 && cells[0].is_empty() {
+            continue;
+        }
+        if cells.len() != header.len() {
+            return Err(format!(
+                "line {}: expected {} columns, found {}",
+                line,
+                header.len(),
+                cells.len()
+            ));
+        }
+        let fields = header
+            .iter()
+            .zip(cells)
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(key, value)| Field {
+                key: key.clone(),
+                value,
+                line,
+            })
+            .collect();
+        records.push(Record { line, fields });
+    }
+    Ok(records)
+}
+
+fn parse_csv_row(cursor: &mut Cursor) -> Result<Option<(usize, Vec<String>)>, String> {
+    if cursor.peek().is_none() {
+        return Ok(None);
+    }
+    let line = cursor.line();
+    let mut cells = vec![String::new()];
+    let mut quoted = false;
+    while let Some(c) = cursor.bump() {
+        let cell = cells.last_mut().unwrap();
+        match (quoted, c) {
+            (true, '"') if cursor.peek() == Some('"') => {
+                cursor.bump();
+                cell.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => cell.push(c),
+            (false, '"') if cell.is_empty() => quoted = true,
+            (false, ',') => cells.push(String::new()),
+            (false, '\r') => {}
+            (false, '\n') => return Ok(Some((line, cells))),
+            (false, c) => cell.push(c),
+        }
+    }
+    if quoted {
+        return cursor.error("unterminated quoted cell");
+    }
+    Ok(Some((line, cells)))
+}
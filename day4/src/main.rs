@@ -1,125 +1,126 @@
+mod diagnostics;
+mod formats;
+mod passport;
+mod schema;
+
+use passport::{Field, Passport, Record};
 use prelude::*;
+use schema::Schema;
 
-type Passport = Vec<(String, String)>;
-
-fn parse(input: &str) -> Vec<Passport> {
-    input
-        .split("\n\n")
-        .map(|pass: &str| {
-            let xs: Passport = pass
-                .replace("\r", "")
-                .split(" ")
-                .flat_map(|x| x.split("\n"))
-                .filter(|&x| x != "")
-                .map(|kvp| {
-                    println!("{}", kvp);
-                    let (k, v) = kvp.split_once(':').unwrap();
-                    (k.to_string(), v.to_string())
-                })
-                .collect();
-            xs
-        })
-        .collect()
+fn parse(input: &str) -> Vec<Record> {
+    let mut records: Vec<Record> = Vec::new();
+    let mut current: Option<Record> = None;
+    for (idx, line) in input.split('\n').enumerate() {
+        let line_no = idx + 1;
+        let line = line.replace("\r", "");
+        if line.trim().is_empty() {
+            records.extend(current.take());
+            continue;
+        }
+        let record = current.get_or_insert_with(|| Record {
+            line: line_no,
+            fields: Vec::new(),
+        });
+        for kvp in line.split_whitespace() {
+            // A pair without a colon is kept as a key with an empty value so that it
+            // shows up as a validation error instead of aborting the whole run.
+            let (k, v) = kvp.split_once(':').unwrap_or((kvp, ""));
+            record.fields.push(Field {
+                key: k.to_string(),
+                value: v.to_string(),
+                line: line_no,
+            });
+        }
+    }
+    records.extend(current);
+    records
 }
 
-fn has_required_fields(passports: &Passport) -> bool {
-    ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"]
+fn part1(schema: &Schema, passports: &[Record]) -> usize {
+    passports
         .iter()
-        .all(|&k| passports.iter().any(|(k2, _)| k2 == k))
-}
-
-fn is_valid(passports: &Passport) -> bool {
-    if !has_required_fields(passports) {
-        return false;
-    };
-    for (k, v) in passports {
-        match k as &str {
-            "byr" => match v.parse::<u32>() {
-                Ok(v) if v < 1920 || v > 2002 => return false,
-                Err(_) => return false,
-                _ => {}
-            },
-            "iyr" => match v.parse::<u32>() {
-                Ok(v) if v < 2010 || v > 2020 => return false,
-                Err(_) => return false,
-                _ => {}
-            },
-            "eyr" => match v.parse::<u32>() {
-                Ok(v) if v < 2020 || v > 2030 => return false,
-                Err(_) => return false,
-                _ => {}
-            },
-            "hgt" => {
-                if v.len() < 2 {
-                    return false;
-                }
-                let unit_type = &v[(v.len() - 2)..];
-                let v = match (&v[0..v.len() - 2]).parse::<u32>() {
-                    Ok(v) => v,
-                    _ => return false,
-                };
-                match unit_type {
-                    "cm" => {
-                        if v < 150 || v > 193 {
-                            return false;
-                        }
-                    }
-                    "in" => {
-                        if v < 59 || v > 76 {
-                            return false;
-                        }
-                    }
-                    _ => return false,
-                };
-            }
-            "hcl" => {
-                if v.len() != 7
-                    || v.chars().nth(0).unwrap() != '#'
-                    || !v.chars().skip(1).all(|c| c.is_digit(16))
-                {
-                    return false;
-                }
-            }
-            "ecl" => match v as &str {
-                "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth" => {}
-                _ => return false,
-            },
-            "pid" => {
-                if v.len() != 9 || !v.chars().all(|c| c.is_digit(10)) {
-                    return false;
-                }
-            }
-            _ => {}
-        };
-    }
-    true
+        .filter(|&x| schema.has_required_fields(x))
+        .count()
 }
 
-fn part1(passports: &[Passport]) -> usize {
-    passports.iter().filter(|&x| has_required_fields(x)).count()
+fn part2(schema: &Schema, passports: &[Record]) -> usize {
+    passports.iter().filter(|&x| schema.is_valid(x)).count()
 }
 
-fn part2(passports: &[Passport]) -> usize {
-    passports.iter().filter(|&x| is_valid(x)).count()
+fn read_records(path: &str, format: Option<&str>) -> Result<Vec<Record>, String> {
+    let format = format.unwrap_or_else(|| match path.rsplit_once('.') {
+        Some((_, ext)) => ext,
+        None => "txt",
+    });
+    let input = read_input(path);
+    match format {
+        "json" => formats::parse_json(&input),
+        "csv" => formats::parse_csv(&input),
+        "txt" | "text" => Ok(parse(&input)),
+        _ => Err(format!("unknown input format {:?}", format)),
+    }
 }
 
 fn main() {
-    let input = read_input("input.txt");
-    let passports = parse(&input);
+    init_verbosity();
+    let args: Vec<String> = std::env::args().collect();
+    let schema = Schema::parse(&read_input("schema.txt")).unwrap();
+    let input_path = arg_value(&args, "--input").unwrap_or("input.txt");
+    let passports = read_records(input_path, arg_value(&args, "--format")).unwrap();
 
     for p in passports.iter() {
-        println!("{:?}", p);
+        verbose!("line {}: {}", p.line, p);
+    }
+
+    if args.iter().any(|a| a == "--diagnostics") {
+        let diagnostics = diagnostics::validate(&passports, &schema);
+        for d in diagnostics.iter() {
+            println!("{}", d);
+        }
+        println!();
+        println!("Most common failures");
+        for (reason, count) in diagnostics::summary(&diagnostics) {
+            println!("{:>6}  {}", count, reason);
+        }
+        println!("=======================");
+    }
+
+    if args.iter().any(|a| a == "--normalize") {
+        for record in passports.iter() {
+            let normalized = Passport::from_record(record, &schema)
+                .and_then(|passport| passport.normalize().map_err(|error| vec![error]));
+            match normalized {
+                Ok(passport) => println!("{}", passport),
+                Err(errors) if schema.is_valid(record) => {
+                    let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                    verbose!(
+                        "line {}: can't normalize: {}",
+                        record.line,
+                        errors.join(", ")
+                    );
+                }
+                Err(_) => {}
+            }
+        }
+        println!("=======================");
     }
 
     println!("Part 1");
-    println!("Answer: {}", part1(&passports));
+    println!("Answer: {}", part1(&schema, &passports));
     println!("=======================");
     println!("Part 2");
-    println!("Answer: {}", part2(&passports));
+    println!("Answer: {}", part2(&schema, &passports));
 }
 
 #[cfg(test)]
 mod tests {
+    use super::passport::{EyeColor, FieldError, Height, HeightUnit, Passport, Rgb};
+    use super::schema::{Rejection, Schema};
+
+    fn get_schema() -> Schema {
+        Schema::parse(include_str!("../schema.txt")).unwrap()
+    }
+
     #[test]
     fn part1() {
         let passports = super::parse(
@@ -138,7 +139,7 @@ hcl:#cfa07d eyr:2025 pid:166559648
 iyr:2011 ecl:brn hgt:59in",
         );
 
-        assert_eq!(2, super::part1(&passports));
+        assert_eq!(2, super::part1(&get_schema(), &passports));
     }
 
     #[test]
@@ -172,7 +173,287 @@ eyr:2022
 
 iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719",
         );
-        assert_eq!(0, super::part2(&invalid_passports));
-        assert_eq!(valid_passports.len(), super::part2(&valid_passports));
+        assert_eq!(0, super::part2(&get_schema(), &invalid_passports));
+        assert_eq!(
+            valid_passports.len(),
+            super::part2(&get_schema(), &valid_passports)
+        );
+    }
+
+    #[test]
+    fn schema_policy() {
+        let schema = Schema::parse(
+            "ecl required enum amb vio
+hgt optional measure cm:100-200",
+        )
+        .unwrap();
+        let ecl = &schema.rule("ecl").unwrap().field_type;
+        let hgt = &schema.rule("hgt").unwrap().field_type;
+        assert_eq!(Ok(()), ecl.check("vio"));
+        assert_eq!(Err(Rejection::NotAllowed), ecl.check("gry"));
+        assert_eq!(Ok(()), hgt.check("100cm"));
+        assert_eq!(Err(Rejection::OutOfRange), hgt.check("99cm"));
+        assert_eq!(Err(Rejection::BadFormat), hgt.check("100in"));
+
+        assert!(Schema::parse("byr required int 1920").is_err());
+        assert!(Schema::parse("byr sometimes any").is_err());
+        assert!(Schema::parse("byr required any\nbyr optional any").is_err());
+    }
+
+    #[test]
+    fn typed_passport() {
+        let schema = get_schema();
+        let records = super::parse(
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+pid:087499704 hgt:74ft ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f iyr:2013 xyz:1",
+        );
+
+        let passport = Passport::from_record(&records[0], &schema).unwrap();
+        assert_eq!(
+            Height {
+                value: 74,
+                unit: HeightUnit::In
+            },
+            passport.height
+        );
+        assert_eq!(
+            Rgb {
+                r: 0x62,
+                g: 0x3a,
+                b: 0x2f
+            },
+            passport.hair_color.0
+        );
+        assert_eq!(EyeColor::Green, passport.eye_color);
+        assert_eq!("087499704", passport.passport_id.0);
+        assert_eq!(None, passport.country_id);
+
+        let errors = Passport::from_record(&records[1], &schema).unwrap_err();
+        assert_eq!(
+            vec![
+                FieldError::Invalid {
+                    field: "hgt".to_string(),
+                    value: "74ft".to_string(),
+                    reason: Rejection::BadFormat,
+                },
+                FieldError::Duplicate("iyr".to_string()),
+                FieldError::Unknown("xyz".to_string()),
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn diagnostics() {
+        let records = super::parse(
+            "eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+
+hgt:59cm ecl:zzz
+eyr:2038 hcl:74454a iyr:2023
+pid:3556412378",
+        );
+        let diagnostics = super::diagnostics::validate(&records, &get_schema());
+        let lines: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            vec![
+                "passport 1 (line 1): eyr: out of range (\"1972\")",
+                "passport 1 (line 2): hgt: bad format (\"170\")",
+                "passport 1 (line 2): pid: bad format (\"186cm\")",
+                "passport 2 (line 4): hcl: bad format (\"dab227\")",
+                "passport 3 (line 7): hgt: out of range (\"59cm\")",
+                "passport 3 (line 7): ecl: value not allowed (\"zzz\")",
+                "passport 3 (line 8): eyr: out of range (\"2038\")",
+                "passport 3 (line 8): hcl: bad format (\"74454a\")",
+                "passport 3 (line 8): iyr: out of range (\"2023\")",
+                "passport 3 (line 9): pid: bad format (\"3556412378\")",
+                "passport 3 (line 7): byr: missing field",
+            ],
+            lines
+        );
+        let summary = super::diagnostics::summary(&diagnostics);
+        assert_eq!(("eyr: out of range".to_string(), 2), summary[0]);
+        assert_eq!(("hcl: bad format".to_string(), 2), summary[1]);
+    }
+
+    #[test]
+    fn normalize_round_trip() {
+        let schema = get_schema();
+        let records = super::parse(
+            "hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 pid:087499704
+hcl:#623A2F cid:88
+
+hcl:#888785
+hgt:164cm byr:2001 iyr:2015
+pid:545766238 ecl:hzl
+eyr:2022",
+        );
+        let canonical: Vec<String> = records
+            .iter()
+            .map(|r| {
+                Passport::from_record(r, &schema)
+                    .unwrap()
+                    .normalize()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                "byr:1980 iyr:2012 eyr:2030 hgt:188cm hcl:#623a2f ecl:grn pid:087499704 cid:88",
+                "byr:2001 iyr:2015 eyr:2022 hgt:164cm hcl:#888785 ecl:hzl pid:545766238",
+            ],
+            canonical
+        );
+
+        let reparsed = super::parse(&canonical.join("\n\n"));
+        for (record, original) in reparsed.iter().zip(records.iter()) {
+            let original = Passport::from_record(original, &schema)
+                .unwrap()
+                .normalize()
+                .unwrap();
+            assert_eq!(original, Passport::from_record(record, &schema).unwrap());
+        }
+    }
+
+    #[test]
+    fn json_and_csv_formats() {
+        let schema = get_schema();
+        let text = super::parse(
+            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in",
+        );
+        let json = super::formats::parse_json(
+            r##"[
+  {"ecl": "gry", "pid": "860033327", "eyr": 2020, "hcl": "#fffffd",
+   "byr": 1937, "iyr": 2017, "cid": 147, "hgt": "183cm"},
+  {"hcl": "#cfa07d", "eyr": 2025, "pid": "166559648", "byr": null,
+   "iyr": 2011, "ecl": "brn", "hgt": "59in"}
+]"##,
+        )
+        .unwrap();
+        let csv = super::formats::parse_csv(
+            "ecl,pid,eyr,hcl,byr,iyr,cid,hgt
+gry,860033327,2020,#fffffd,1937,2017,147,183cm
+brn,\"166559648\",2025,#cfa07d,,2011,,59in
+",
+        )
+        .unwrap();
+
+        for records in [&text, &json, &csv].iter() {
+            assert_eq!(2, records.len());
+            assert_eq!(1, super::part1(&schema, records));
+            assert_eq!(1, super::part2(&schema, records));
+        }
+        assert_eq!(
+            Passport::from_record(&text[0], &schema),
+            Passport::from_record(&json[0], &schema)
+        );
+        assert_eq!(
+            Passport::from_record(&text[0], &schema),
+            Passport::from_record(&csv[0], &schema)
+        );
+        assert_eq!(4, json[1].line);
+        assert_eq!(3, csv[1].line);
+
+        let err = super::formats::parse_json("[{\"byr\": [1]}]").unwrap_err();
+        assert_eq!("line 1, column 10: nested values are not supported", err);
+        let err = super::formats::parse_csv("byr,iyr\n1920\n").unwrap_err();
+        assert_eq!("line 2: expected 2 columns, found 1", err);
+    }
+
+    #[test]
+    fn relaxed_schema() {
+        let schema = Schema::parse(
+            &include_str!("../schema.txt")
+                .replace("byr required", "byr optional")
+                .replace("in:59-76", "in:59-76 mm:1500-1930"),
+        )
+        .unwrap();
+        let records = super::parse(
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 hcl:#623a2f
+
+pid:087499704 hgt:1700mm ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f",
+        );
+        assert_eq!(2, super::part2(&schema, &records));
+        assert!(super::diagnostics::validate(&records, &schema).is_empty());
+
+        // The typed model can't hold these, but that doesn't change what's valid.
+        assert_eq!(
+            Err(vec![FieldError::Missing("byr".to_string())]),
+            Passport::from_record(&records[0], &schema)
+        );
+        assert_eq!(
+            Err(vec![FieldError::Invalid {
+                field: "hgt".to_string(),
+                value: "1700mm".to_string(),
+                reason: Rejection::BadFormat,
+            }]),
+            Passport::from_record(&records[1], &schema)
+        );
+    }
+
+    #[test]
+    fn unknown_and_repeated_fields() {
+        let records = super::parse(
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f xyz:1
+
+pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f iyr:2013",
+        );
+        let schema = get_schema();
+        assert_eq!(2, super::part2(&schema, &records));
+        let diagnostics = super::diagnostics::validate(&records, &schema);
+        assert_eq!(
+            vec![
+                FieldError::Unknown("xyz".to_string()),
+                FieldError::Duplicate("iyr".to_string())
+            ],
+            diagnostics.into_iter().map(|d| d.error).collect::<Vec<_>>()
+        );
+
+        let strict = Schema::parse(&format!("strict\n{}", include_str!("../schema.txt"))).unwrap();
+        assert_eq!(0, super::part2(&strict, &records));
+    }
+
+    #[test]
+    fn normalize_huge_height() {
+        let schema =
+            Schema::parse(&include_str!("../schema.txt").replace("in:59-76", "in:0-4294967295"))
+                .unwrap();
+        let records = super::parse(
+            "pid:087499704 hgt:4294967295in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f",
+        );
+        let passport = Passport::from_record(&records[0], &schema).unwrap();
+        assert_eq!(
+            Err(FieldError::Invalid {
+                field: "hgt".to_string(),
+                value: "4294967295in".to_string(),
+                reason: Rejection::OutOfRange,
+            }),
+            passport.normalize()
+        );
+        // Fits in cm even though the intermediate product doesn't fit a u32.
+        assert_eq!(
+            Some(Height {
+                value: 2_540_000_000,
+                unit: HeightUnit::Cm
+            }),
+            Height {
+                value: 1_000_000_000,
+                unit: HeightUnit::In
+            }
+            .to_cm()
+        );
     }
 }
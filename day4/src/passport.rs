@@ -0,0 +1,356 @@
+use crate::schema::{Rejection, Schema};
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub key: String,
+    pub value: String,
+    pub line: usize,
+}
+
+// A passport as read from the input, before any validation. `line` is where it starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub line: usize,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BirthYear(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IssueYear(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpirationYear(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeightUnit {
+    Cm,
+    In,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Height {
+    pub value: u32,
+    pub unit: HeightUnit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HairColor(pub Rgb);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EyeColor {
+    Amber,
+    Blue,
+    Brown,
+    Gray,
+    Green,
+    Hazel,
+    Other,
+    // A colour the schema accepts that has no variant of its own.
+    Custom(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassportId(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountryId(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Passport {
+    pub birth_year: BirthYear,
+    pub issue_year: IssueYear,
+    pub expiration_year: ExpirationYear,
+    pub height: Height,
+    pub hair_color: HairColor,
+    pub eye_color: EyeColor,
+    pub passport_id: PassportId,
+    pub country_id: Option<CountryId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldError {
+    Missing(String),
+    Duplicate(String),
+    Unknown(String),
+    Invalid {
+        field: String,
+        value: String,
+        reason: Rejection,
+    },
+}
+
+impl FieldError {
+    pub fn field(&self) -> &str {
+        match self {
+            FieldError::Missing(field)
+            | FieldError::Duplicate(field)
+            | FieldError::Unknown(field)
+            | FieldError::Invalid { field, .. } => field,
+        }
+    }
+
+    pub fn reason(&self) -> &'static str {
+        match self {
+            FieldError::Missing(_) => "missing field",
+            FieldError::Duplicate(_) => "duplicate field",
+            FieldError::Unknown(_) => "unknown field",
+            FieldError::Invalid { reason, .. } => match reason {
+                Rejection::BadFormat => "bad format",
+                Rejection::OutOfRange => "out of range",
+                Rejection::NotAllowed => "value not allowed",
+            },
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldError::Invalid { field, value, .. } => {
+                write!(f, "{}: {} ({:?})", field, self.reason(), value)
+            }
+            _ => write!(f, "{}: {}", self.field(), self.reason()),
+        }
+    }
+}
+
+impl Height {
+    fn parse(s: &str) -> Option<Self> {
+        let (value, unit) = if let Some(v) = s.strip_suffix("cm") {
+            (v, HeightUnit::Cm)
+        } else if let Some(v) = s.strip_suffix("in") {
+            (v, HeightUnit::In)
+        } else {
+            return None;
+        };
+        let value = value.parse::<u32>().ok()?;
+        Some(Self { value, unit })
+    }
+}
+
+impl Height {
+    // `None` when the height in cm doesn't fit a `u32`.
+    pub fn to_cm(self) -> Option<Self> {
+        match self.unit {
+            HeightUnit::Cm => Some(self),
+            HeightUnit::In => {
+                let cm = (u64::from(self.value) * 254 + 50) / 100;
+                Some(Self {
+                    value: u32::try_from(cm).ok()?,
+                    unit: HeightUnit::Cm,
+                })
+            }
+        }
+    }
+}
+
+impl Rgb {
+    fn parse(s: &str) -> Option<Self> {
+        let hex = s.strip_prefix('#')?;
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Self {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        })
+    }
+}
+
+impl EyeColor {
+    fn parse(s: &str) -> Self {
+        match s {
+            "amb" => EyeColor::Amber,
+            "blu" => EyeColor::Blue,
+            "brn" => EyeColor::Brown,
+            "gry" => EyeColor::Gray,
+            "grn" => EyeColor::Green,
+            "hzl" => EyeColor::Hazel,
+            "oth" => EyeColor::Other,
+            _ => EyeColor::Custom(s.to_string()),
+        }
+    }
+
+    fn code(&self) -> &str {
+        match self {
+            EyeColor::Amber => "amb",
+            EyeColor::Blue => "blu",
+            EyeColor::Brown => "brn",
+            EyeColor::Gray => "gry",
+            EyeColor::Green => "grn",
+            EyeColor::Hazel => "hzl",
+            EyeColor::Other => "oth",
+            EyeColor::Custom(s) => s,
+        }
+    }
+}
+
+impl PassportId {
+    fn parse(s: &str) -> Option<Self> {
+        if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+            Some(Self(s.to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, field) in self.fields.iter().enumerate() {
+            if idx > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}:{}", field.key, field.value)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.unit {
+            HeightUnit::Cm => "cm",
+            HeightUnit::In => "in",
+        };
+        write!(f, "{}{}", self.value, unit)
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl fmt::Display for Passport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "byr:{} iyr:{} eyr:{} hgt:{} hcl:{} ecl:{} pid:{}",
+            self.birth_year.0,
+            self.issue_year.0,
+            self.expiration_year.0,
+            self.height,
+            self.hair_color.0,
+            self.eye_color.code(),
+            self.passport_id.0
+        )?;
+        if let Some(cid) = &self.country_id {
+            write!(f, " cid:{}", cid.0)?;
+        }
+        Ok(())
+    }
+}
+
+impl Passport {
+    // Heights in cm; `Display` already writes fields in a fixed order with lowercase hex,
+    // so a normalized passport prints as its canonical line.
+    pub fn normalize(&self) -> Result<Self, FieldError> {
+        let height = self.height.to_cm().ok_or_else(|| FieldError::Invalid {
+            field: "hgt".to_string(),
+            value: self.height.to_string(),
+            reason: Rejection::OutOfRange,
+        })?;
+        Ok(Self {
+            height,
+            ..self.clone()
+        })
+    }
+
+    // The record must pass the schema before it is converted. The schema decides what
+    // is valid; this only fails on top of that when a passport the schema accepts can't
+    // be represented here, e.g. a missing `byr` under a schema that makes it optional,
+    // or a height unit other than cm or in. Fields the schema defines beyond the ones
+    // modelled here are dropped.
+    pub fn from_record(record: &Record, schema: &Schema) -> Result<Self, Vec<FieldError>> {
+        schema.check_record(record)?;
+        let mut errors: Vec<FieldError> = Vec::new();
+
+        let mut birth_year = None;
+        let mut issue_year = None;
+        let mut expiration_year = None;
+        let mut height = None;
+        let mut hair_color = None;
+        let mut eye_color = None;
+        let mut passport_id = None;
+        let mut country_id = None;
+
+        for field in &record.fields {
+            let (k, v) = (&field.key, &field.value);
+            let parsed = match k as &str {
+                "byr" => v.parse().ok().map(|x| birth_year = Some(BirthYear(x))),
+                "iyr" => v.parse().ok().map(|x| issue_year = Some(IssueYear(x))),
+                "eyr" => v
+                    .parse()
+                    .ok()
+                    .map(|x| expiration_year = Some(ExpirationYear(x))),
+                "hgt" => Height::parse(v).map(|x| height = Some(x)),
+                "hcl" => Rgb::parse(v).map(|x| hair_color = Some(HairColor(x))),
+                "ecl" => {
+                    eye_color = Some(EyeColor::parse(v));
+                    Some(())
+                }
+                "pid" => PassportId::parse(v).map(|x| passport_id = Some(x)),
+                "cid" => {
+                    country_id = Some(CountryId(v.clone()));
+                    Some(())
+                }
+                _ => Some(()),
+            };
+            if parsed.is_none() {
+                errors.push(FieldError::Invalid {
+                    field: k.clone(),
+                    value: v.clone(),
+                    reason: Rejection::BadFormat,
+                });
+            }
+        }
+
+        let modelled = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
+        for &name in modelled.iter() {
+            if !record.fields.iter().any(|f| f.key == name) {
+                errors.push(FieldError::Missing(name.to_string()));
+            }
+        }
+
+        match (
+            birth_year,
+            issue_year,
+            expiration_year,
+            height,
+            hair_color,
+            eye_color,
+            passport_id,
+        ) {
+            (Some(byr), Some(iyr), Some(eyr), Some(hgt), Some(hcl), Some(ecl), Some(pid))
+                if errors.is_empty() =>
+            {
+                Ok(Self {
+                    birth_year: byr,
+                    issue_year: iyr,
+                    expiration_year: eyr,
+                    height: hgt,
+                    hair_color: hcl,
+                    eye_color: ecl,
+                    passport_id: pid,
+                    country_id,
+                })
+            }
+            _ => Err(errors),
+        }
+    }
+}
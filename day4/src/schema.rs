@@ -0,0 +1,226 @@
+use crate::passport::{FieldError, Record};
+use regex::Regex;
+
+pub enum FieldType {
+    Any,
+    Int { min: u32, max: u32 },
+    Measure(Vec<(String, u32, u32)>),
+    HexColor,
+    Enum(Vec<String>),
+    Regex(Regex),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rejection {
+    BadFormat,
+    OutOfRange,
+    NotAllowed,
+}
+
+fn check_range(v: u32, min: u32, max: u32) -> Result<(), Rejection> {
+    if (min..=max).contains(&v) {
+        Ok(())
+    } else {
+        Err(Rejection::OutOfRange)
+    }
+}
+
+impl FieldType {
+    pub fn check(&self, value: &str) -> Result<(), Rejection> {
+        match self {
+            FieldType::Any => Ok(()),
+            FieldType::Int { min, max } => match value.parse::<u32>() {
+                Ok(v) => check_range(v, *min, *max),
+                Err(_) => Err(Rejection::BadFormat),
+            },
+            FieldType::Measure(units) => {
+                let mut result = Err(Rejection::BadFormat);
+                for (unit, min, max) in units {
+                    if let Some(Ok(v)) = value.strip_suffix(unit as &str).map(|v| v.parse::<u32>())
+                    {
+                        result = check_range(v, *min, *max);
+                        if result.is_ok() {
+                            break;
+                        }
+                    }
+                }
+                result
+            }
+            FieldType::HexColor => match value.strip_prefix('#') {
+                Some(hex) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => Ok(()),
+                _ => Err(Rejection::BadFormat),
+            },
+            FieldType::Enum(values) => {
+                if values.iter().any(|v| v == value) {
+                    Ok(())
+                } else {
+                    Err(Rejection::NotAllowed)
+                }
+            }
+            FieldType::Regex(re) => {
+                if re.is_match(value) {
+                    Ok(())
+                } else {
+                    Err(Rejection::BadFormat)
+                }
+            }
+        }
+    }
+}
+
+pub struct FieldRule {
+    pub name: String,
+    pub required: bool,
+    pub field_type: FieldType,
+}
+
+// Unknown and repeated fields are reported but only make a passport invalid when the
+// schema is `strict`.
+pub struct Schema {
+    rules: Vec<FieldRule>,
+    strict: bool,
+}
+
+fn parse_range(s: &str) -> Result<(u32, u32), String> {
+    let (min, max) = s
+        .split_once('-')
+        .ok_or_else(|| format!("expected `<min>-<max>`, got {:?}", s))?;
+    let min = min.parse::<u32>().map_err(prelude::stringify_err)?;
+    let max = max.parse::<u32>().map_err(prelude::stringify_err)?;
+    Ok((min, max))
+}
+
+fn parse_field_type(name: &str, args: &[&str]) -> Result<FieldType, String> {
+    match (name, args) {
+        ("any", []) => Ok(FieldType::Any),
+        ("int", [range]) => {
+            let (min, max) = parse_range(range)?;
+            Ok(FieldType::Int { min, max })
+        }
+        ("measure", units) if !units.is_empty() => units
+            .iter()
+            .map(|arg| {
+                let (unit, range) = arg
+                    .split_once(':')
+                    .ok_or_else(|| format!("expected `<unit>:<min>-<max>`, got {:?}", arg))?;
+                let (min, max) = parse_range(range)?;
+                Ok((unit.to_string(), min, max))
+            })
+            .collect::<Result<Vec<_>, String>>()
+            .map(FieldType::Measure),
+        ("hexcolor", []) => Ok(FieldType::HexColor),
+        ("enum", values) if !values.is_empty() => Ok(FieldType::Enum(
+            values.iter().map(|v| v.to_string()).collect(),
+        )),
+        ("regex", [pattern]) => Regex::new(pattern)
+            .map(FieldType::Regex)
+            .map_err(prelude::stringify_err),
+        _ => Err(format!("invalid type `{}` with arguments {:?}", name, args)),
+    }
+}
+
+impl Schema {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut rules: Vec<FieldRule> = Vec::new();
+        let mut strict = false;
+        for (line_idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line == "strict" {
+                strict = true;
+                continue;
+            }
+            let err = |msg: String| format!("schema line {}: {}", line_idx + 1, msg);
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let (name, required, type_name, args) = match &parts[..] {
+                [name, required, type_name, args @ ..] => (*name, *required, *type_name, args),
+                _ => return Err(err("expected `<field> <required|optional> <type>`".into())),
+            };
+            let required = match required {
+                "required" => true,
+                "optional" => false,
+                _ => {
+                    return Err(err(format!(
+                        "expected required or optional, got {:?}",
+                        required
+                    )))
+                }
+            };
+            if rules.iter().any(|r| r.name == name) {
+                return Err(err(format!("field {:?} is defined twice", name)));
+            }
+            let field_type = parse_field_type(type_name, args).map_err(err)?;
+            rules.push(FieldRule {
+                name: name.to_string(),
+                required,
+                field_type,
+            });
+        }
+        Ok(Self { rules, strict })
+    }
+
+    pub fn rule(&self, name: &str) -> Option<&FieldRule> {
+        self.rules.iter().find(|r| r.name == name)
+    }
+
+    pub fn required_fields(&self) -> impl Iterator<Item = &str> {
+        self.rules
+            .iter()
+            .filter(|r| r.required)
+            .map(|r| &r.name as &str)
+    }
+
+    // Every problem with the record's fields under this schema, in field order with
+    // missing fields last.
+    pub fn check_record(&self, record: &Record) -> Result<(), Vec<FieldError>> {
+        let mut errors: Vec<FieldError> = Vec::new();
+        let mut seen: Vec<&str> = Vec::new();
+        for field in &record.fields {
+            let (k, v) = (&field.key, &field.value);
+            if seen.contains(&(k as &str)) {
+                errors.push(FieldError::Duplicate(k.clone()));
+                continue;
+            }
+            seen.push(k);
+            match self.rule(k) {
+                Some(rule) => {
+                    if let Err(reason) = rule.field_type.check(v) {
+                        errors.push(FieldError::Invalid {
+                            field: k.clone(),
+                            value: v.clone(),
+                            reason,
+                        });
+                    }
+                }
+                None => errors.push(FieldError::Unknown(k.clone())),
+            }
+        }
+        for name in self.required_fields() {
+            if !seen.contains(&name) {
+                errors.push(FieldError::Missing(name.to_string()));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn is_valid(&self, record: &Record) -> bool {
+        match self.check_record(record) {
+            Ok(()) => true,
+            Err(errors) => errors.iter().all(|error| match error {
+                FieldError::Unknown(_) | FieldError::Duplicate(_) => !self.strict,
+                _ => false,
+            }),
+        }
+    }
+
+    pub fn has_required_fields(&self, record: &Record) -> bool {
+        self.required_fields()
+            .all(|name| record.fields.iter().any(|f| f.key == name))
+    }
+}
@@ -0,0 +1,155 @@
+use crate::layout::{SeatLayout, SeatPosition};
+use std::collections::HashSet;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preference {
+    Window,
+    Aisle,
+    Any,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub name: String,
+    pub party_size: u32,
+    pub preference: Preference,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    pub name: String,
+    pub seats: Vec<SeatPosition>,
+    pub codes: Vec<String>,
+    pub preference_met: bool,
+}
+
+// One request per line: `<name> <party size> [window|aisle|any]`.
+pub fn parse_requests(text: &str) -> Result<Vec<Request>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            let err = |msg: &str| format!("request line {}: {}: {:?}", idx + 1, msg, line);
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let (name, size, preference) = match parts[..] {
+                [name, size] => (name, size, "any"),
+                [name, size, preference] => (name, size, preference),
+                _ => return Err(err("expected `<name> <party size> [preference]`")),
+            };
+            let party_size = match size.parse::<u32>() {
+                Ok(n) if n > 0 => n,
+                _ => return Err(err("invalid party size")),
+            };
+            let preference = match preference {
+                "window" => Preference::Window,
+                "aisle" => Preference::Aisle,
+                "any" => Preference::Any,
+                _ => return Err(err("unknown preference")),
+            };
+            Ok(Request {
+                name: name.to_string(),
+                party_size,
+                preference,
+            })
+        })
+        .collect()
+}
+
+// Seats parties front to back. A party always sits in one row without crossing an aisle;
+// the first block that satisfies the preference wins, else the first free block.
+pub struct SeatAllocator<'a> {
+    layout: &'a SeatLayout,
+    rows: Range<u32>,
+    aisles: Vec<u32>,
+    taken: HashSet<u32>,
+}
+
+impl<'a> SeatAllocator<'a> {
+    pub fn new(layout: &'a SeatLayout, rows: Range<u32>, aisles: Vec<u32>) -> Self {
+        Self {
+            layout,
+            rows,
+            aisles,
+            taken: HashSet::new(),
+        }
+    }
+
+    pub fn occupy(&mut self, id: u32) {
+        self.taken.insert(id);
+    }
+
+    // Runs of columns between aisles.
+    fn blocks(&self) -> Vec<Range<u32>> {
+        let mut starts: Vec<u32> = self
+            .aisles
+            .iter()
+            .copied()
+            .filter(|&a| a > 0 && a < self.layout.columns())
+            .collect();
+        starts.push(0);
+        starts.push(self.layout.columns());
+        starts.sort_unstable();
+        starts.dedup();
+        starts.windows(2).map(|w| w[0]..w[1]).collect()
+    }
+
+    fn satisfies(&self, columns: &Range<u32>, block: &Range<u32>, preference: Preference) -> bool {
+        let last_column = self.layout.columns() - 1;
+        match preference {
+            Preference::Any => true,
+            Preference::Window => columns.start == 0 || columns.end - 1 == last_column,
+            Preference::Aisle => {
+                (columns.start == block.start && block.start != 0)
+                    || (columns.end == block.end && block.end - 1 != last_column)
+            }
+        }
+    }
+
+    pub fn assign(&mut self, request: &Request) -> Option<Assignment> {
+        let mut fallback: Option<(u32, Range<u32>)> = None;
+        let mut chosen: Option<(u32, Range<u32>)> = None;
+        'search: for row in self.rows.clone() {
+            for block in self.blocks() {
+                if block.end - block.start < request.party_size {
+                    continue;
+                }
+                for start in block.start..=block.end - request.party_size {
+                    let columns = start..start + request.party_size;
+                    let free = columns.clone().all(|column| {
+                        !self
+                            .taken
+                            .contains(&self.layout.id(&SeatPosition { row, column }))
+                    });
+                    if !free {
+                        continue;
+                    }
+                    if self.satisfies(&columns, &block, request.preference) {
+                        chosen = Some((row, columns));
+                        break 'search;
+                    }
+                    if fallback.is_none() {
+                        fallback = Some((row, columns));
+                    }
+                }
+            }
+        }
+
+        let preference_met = chosen.is_some();
+        let (row, columns) = chosen.or(fallback)?;
+        let seats: Vec<SeatPosition> = columns.map(|column| SeatPosition { row, column }).collect();
+        for seat in seats.iter() {
+            self.taken.insert(self.layout.id(seat));
+        }
+        let codes = seats
+            .iter()
+            .map(|seat| self.layout.encode(seat).unwrap())
+            .collect();
+        Some(Assignment {
+            name: request.name.clone(),
+            seats,
+            codes,
+            preference_met,
+        })
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SeatPosition {
+    pub row: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    WrongLength { expected: usize, found: usize },
+    UnexpectedChar { index: usize, ch: char },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::WrongLength { expected, found } => write!(
+                f,
+                "expected a code of {} characters, found {}",
+                expected, found
+            ),
+            DecodeError::UnexpectedChar { index, ch } => {
+                write!(f, "unexpected character {:?} at position {}", ch, index)
+            }
+        }
+    }
+}
+
+// Row characters come first, then column characters. Each character is one bit, most
+// significant first; `row_chars`/`column_chars` are the (0, 1) letters of each part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeatLayout {
+    pub row_bits: u32,
+    pub column_bits: u32,
+    pub row_chars: (char, char),
+    pub column_chars: (char, char),
+}
+
+impl SeatLayout {
+    pub const STANDARD: SeatLayout = SeatLayout {
+        row_bits: 7,
+        column_bits: 3,
+        row_chars: ('F', 'B'),
+        column_chars: ('L', 'R'),
+    };
+
+    // Parses `<row bits>x<column bits>:<row chars>:<column chars>`, e.g. `7x3:FB:LR`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let err = || format!("expected a layout like `7x3:FB:LR`, got {:?}", s);
+        let parts: Vec<&str> = s.split(':').collect();
+        let (bits, row_chars, column_chars) = match parts[..] {
+            [bits, row_chars, column_chars] => (bits, row_chars, column_chars),
+            _ => return Err(err()),
+        };
+        let (row_bits, column_bits) = bits.split_once('x').ok_or_else(err)?;
+        let row_bits: u32 = row_bits.parse().map_err(|_| err())?;
+        let column_bits: u32 = column_bits.parse().map_err(|_| err())?;
+        let pair = |chars: &str| -> Result<(char, char), String> {
+            match chars.chars().collect::<Vec<char>>()[..] {
+                [zero, one] if zero != one => Ok((zero, one)),
+                _ => Err(err()),
+            }
+        };
+        let layout = Self {
+            row_bits,
+            column_bits,
+            row_chars: pair(row_chars)?,
+            column_chars: pair(column_chars)?,
+        };
+        let (r, c) = (layout.row_chars, layout.column_chars);
+        if r.0 == c.0 || r.0 == c.1 || r.1 == c.0 || r.1 == c.1 {
+            return Err(format!("row and column letters overlap in {:?}", s));
+        }
+        let total_bits = row_bits.checked_add(column_bits);
+        if total_bits.is_none_or(|bits| bits > 31) || row_bits == 0 || column_bits == 0 {
+            return Err(format!("unsupported number of bits in {:?}", s));
+        }
+        Ok(layout)
+    }
+
+    pub fn rows(&self) -> u32 {
+        1 << self.row_bits
+    }
+
+    pub fn columns(&self) -> u32 {
+        1 << self.column_bits
+    }
+
+    // A single aisle down the middle; columns before which an aisle runs.
+    pub fn default_aisles(&self) -> Vec<u32> {
+        vec![self.columns() / 2]
+    }
+
+    pub fn code_len(&self) -> usize {
+        (self.row_bits + self.column_bits) as usize
+    }
+
+    pub fn id(&self, seat: &SeatPosition) -> u32 {
+        seat.row * self.columns() + seat.column
+    }
+
+    pub fn position(&self, id: u32) -> SeatPosition {
+        SeatPosition {
+            row: id / self.columns(),
+            column: id % self.columns(),
+        }
+    }
+
+    pub fn decode(&self, code: &str) -> Result<SeatPosition, DecodeError> {
+        let found = code.chars().count();
+        if found != self.code_len() {
+            return Err(DecodeError::WrongLength {
+                expected: self.code_len(),
+                found,
+            });
+        }
+        let mut row = 0;
+        let mut column = 0;
+        for (index, ch) in code.chars().enumerate() {
+            let (value, (zero, one)) = if index < self.row_bits as usize {
+                (&mut row, self.row_chars)
+            } else {
+                (&mut column, self.column_chars)
+            };
+            *value <<= 1;
+            if ch == one {
+                *value |= 1;
+            } else if ch != zero {
+                return Err(DecodeError::UnexpectedChar { index, ch });
+            }
+        }
+        Ok(SeatPosition { row, column })
+    }
+
+    // Decodes straight to a seat ID, reading the code as one binary number. The hot loop
+    // has no per-character branches; invalid codes fall back to `decode` for the error.
+    pub fn decode_id(&self, code: &str) -> Result<u32, DecodeError> {
+        let bytes = code.as_bytes();
+        let ascii = [self.row_chars, self.column_chars]
+            .iter()
+            .all(|(zero, one)| zero.is_ascii() && one.is_ascii());
+        if !ascii || bytes.len() != self.code_len() {
+            return self.decode(code).map(|seat| self.id(&seat));
+        }
+        let (rows, columns) = bytes.split_at(self.row_bits as usize);
+        let mut id = 0u32;
+        let mut valid = true;
+        for (part, (zero, one)) in [(rows, self.row_chars), (columns, self.column_chars)].iter() {
+            let (zero, one) = (*zero as u8, *one as u8);
+            for &b in part.iter() {
+                id = (id << 1) | (b == one) as u32;
+                valid &= (b == one) | (b == zero);
+            }
+        }
+        if valid {
+            Ok(id)
+        } else {
+            self.decode(code).map(|seat| self.id(&seat))
+        }
+    }
+
+    pub fn encode(&self, seat: &SeatPosition) -> Option<String> {
+        if seat.row >= self.rows() || seat.column >= self.columns() {
+            return None;
+        }
+        let bits = |value: u32, count: u32, (zero, one): (char, char)| {
+            (0..count)
+                .rev()
+                .map(move |bit| if value >> bit & 1 == 1 { one } else { zero })
+        };
+        Some(
+            bits(seat.row, self.row_bits, self.row_chars)
+                .chain(bits(seat.column, self.column_bits, self.column_chars))
+                .collect(),
+        )
+    }
+}
@@ -1,106 +1,292 @@
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-struct SeatPosition {
-    row: u8,
-    column: u8,
-}
+mod allocator;
+mod layout;
+mod occupancy;
+mod seat_map;
 
-impl SeatPosition {
-    fn from_partition_code(code: &str) -> Option<Self> {
-        if code.chars().count() != 10 {
-            return None;
-        }
-        let row: u8 = code[..7]
-            .chars()
-            .enumerate()
-            .map(|(idx, c)| match c {
-                'B' => (2 as u8).pow(6 - idx as u32),
-                'F' => 0,
-                _ => panic!("unexpected row code: {:?}", c),
-            })
-            .sum();
-        let column: u8 = code[7..]
-            .chars()
-            .enumerate()
-            .map(|(idx, c)| match c {
-                'R' => (2 as u8).pow(2 - idx as u32),
-                'L' => 0,
-                _ => panic!("unexpected column code: {:?}", c),
-            })
-            .sum();
-        Some(Self { row, column })
-    }
-    fn id(&self) -> u32 {
-        (self.row as u32) * 8 + (self.column as u32)
-    }
-}
+use allocator::{parse_requests, SeatAllocator};
+use layout::SeatLayout;
+use occupancy::{analyze_seats, find_single_gap, SeatError};
+use prelude::arg_value;
+use seat_map::SeatMap;
 
-fn part1(seats: &Vec<SeatPosition>) -> usize {
-    seats.iter().map(|x| x.id() as usize).max().unwrap()
+fn part1(ids: &[u32]) -> usize {
+    ids.iter().copied().max().unwrap() as usize
 }
 
-fn part2(seats: &Vec<SeatPosition>) -> usize {
-    for (prev_idx, seat) in seats[1..seats.len() - 1].iter().enumerate() {
-        let prev = &seats[prev_idx];
-        if prev.id() + 1 != seat.id() {
-            return prev.id() as usize + 1;
-        }
+// Takes the sort-free gap check when the input is one contiguous block with a single gap,
+// and otherwise falls back to the full analysis so the failure can be explained.
+fn part2(layout: &SeatLayout, ids: &[u32]) -> Result<u32, SeatError> {
+    if let Some(id) = find_single_gap(ids) {
+        return Ok(id);
     }
-    panic!("seat not found")
-}
-
-fn print_seat_grid(seats: &Vec<SeatPosition>) {
-    let mut grid: [[bool; 8]; 128] = [[false; 8]; 128];
-    for seat in seats {
-        grid[seat.row as usize][seat.column as usize] = true;
-    }
-    for (row_idx, row) in grid
-        .iter()
-        .enumerate()
-        .filter(|(_, row)| row.iter().any(|&x| x))
-    {
-        print!("{:03}: ", row_idx);
-        for &col in row.iter() {
-            if col {
-                print!("T")
-            } else {
-                print!("_")
-            }
-        }
-        println!()
+    let report = analyze_seats(layout, ids)?;
+    match report.candidates[..] {
+        [id] => Ok(id),
+        [] => Err(SeatError::NoEmptySeat),
+        _ => Err(SeatError::Ambiguous(report.candidates)),
     }
 }
 
 fn main() {
-    let mut seats: Vec<SeatPosition> = prelude::read_input_lines("input.txt")
-        .into_iter()
-        .map(|seat_code| SeatPosition::from_partition_code(&seat_code).unwrap())
+    prelude::init_verbosity();
+    let args: Vec<String> = std::env::args().collect();
+    let layout = match arg_value(&args, "--layout") {
+        Some(layout) => SeatLayout::parse(layout).unwrap(),
+        None => SeatLayout::STANDARD,
+    };
+    let ids: Vec<u32> = prelude::read_input("input.txt")
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|seat_code| layout.decode_id(seat_code).unwrap())
         .collect();
-    seats.sort();
 
-    print_seat_grid(&seats);
-    println!();
     println!("Part 1");
-    println!("Answer: {}", part1(&seats));
+    println!("Answer: {}", part1(&ids));
     println!("=======================");
     println!("Part 2");
-    println!("Answer: {}", part2(&seats));
+    let my_seat = part2(&layout, &ids);
+    match &my_seat {
+        Ok(seat_id) => {
+            let seat_id = *seat_id;
+            let code = layout.encode(&layout.position(seat_id)).unwrap();
+            println!("Answer: {} ({})", seat_id, code);
+        }
+        Err(err) => println!("No answer: {}", err),
+    }
+    let report = analyze_seats(&layout, &ids);
+    if let Ok(report) = &report {
+        if !report.duplicates.is_empty() {
+            println!("Duplicate boarding passes: {:?}", report.duplicates);
+        }
+    }
+    let aisles: Vec<u32> = match arg_value(&args, "--aisles") {
+        Some(aisles) => aisles.split(',').map(|a| a.parse().unwrap()).collect(),
+        None => layout.default_aisles(),
+    };
+
+    let mut ids = ids;
+    if let Some(path) = arg_value(&args, "--assign") {
+        let rows = match &report {
+            Ok(report) => report.front_rows.end..report.back_rows.start,
+            Err(_) => 0..layout.rows(),
+        };
+        let mut allocator = SeatAllocator::new(&layout, rows, aisles.clone());
+        for &id in ids.iter().chain(my_seat.iter()) {
+            allocator.occupy(id);
+        }
+        println!("=======================");
+        println!("Assignments");
+        for request in parse_requests(&prelude::read_input(path)).unwrap() {
+            match allocator.assign(&request) {
+                Some(assignment) => {
+                    let note = if assignment.preference_met {
+                        ""
+                    } else {
+                        " (preference not met)"
+                    };
+                    println!(
+                        "{}: {}{}",
+                        assignment.name,
+                        assignment.codes.join(" "),
+                        note
+                    );
+                    ids.extend(assignment.seats.iter().map(|seat| layout.id(seat)));
+                }
+                None => println!(
+                    "{}: no adjacent seats for {}",
+                    request.name, request.party_size
+                ),
+            }
+        }
+    }
+
+    let seat_map = SeatMap::new(&layout, &ids, my_seat.ok()).with_aisles(aisles);
+    if prelude::is_verbose() {
+        println!();
+        print!("{}", seat_map.to_text());
+    }
+    if let Some(path) = arg_value(&args, "--svg") {
+        std::fs::write(path, seat_map.to_svg()).unwrap();
+        println!("Wrote {}", path);
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::SeatPosition;
+    use super::allocator::{parse_requests, SeatAllocator};
+    use super::layout::{DecodeError, SeatLayout, SeatPosition};
+    use super::occupancy::{find_single_gap, SeatError};
+    use super::seat_map::SeatMap;
     #[test]
     fn part1() {
+        let layout = SeatLayout::STANDARD;
         let test_data = vec![
             ("BFFFBBFRRR", (70, 7, 567)),
             ("FFFBBBFRRR", (14, 7, 119)),
             ("BBFFBBFRLL", (102, 4, 820)),
         ];
         for (seat_code, (row, column, id)) in test_data {
-            let seat = SeatPosition::from_partition_code(seat_code).unwrap();
+            let seat = layout.decode(seat_code).unwrap();
             assert_eq!(row, seat.row);
             assert_eq!(column, seat.column);
-            assert_eq!(id, seat.id());
+            assert_eq!(id, layout.id(&seat));
+            assert_eq!(seat_code, layout.encode(&seat).unwrap());
+            assert_eq!(Ok(id), layout.decode_id(seat_code));
+        }
+    }
+
+    #[test]
+    fn custom_layout() {
+        let layout = SeatLayout::parse("4x2:ab:xy").unwrap();
+        assert_eq!((16, 4), (layout.rows(), layout.columns()));
+        let seat = SeatPosition { row: 9, column: 2 };
+        assert_eq!("baabyx", layout.encode(&seat).unwrap());
+        assert_eq!(Ok(seat), layout.decode("baabyx"));
+        assert_eq!(38, layout.id(&seat));
+        assert_eq!(seat, layout.position(38));
+        assert_eq!(None, layout.encode(&SeatPosition { row: 16, column: 0 }));
+        assert_eq!(
+            Err(DecodeError::UnexpectedChar { index: 4, ch: 'L' }),
+            layout.decode("baabLx")
+        );
+        assert_eq!(
+            Err(DecodeError::UnexpectedChar { index: 4, ch: 'L' }),
+            layout.decode_id("baabLx")
+        );
+        assert_eq!(
+            Err(DecodeError::WrongLength {
+                expected: 6,
+                found: 10
+            }),
+            layout.decode("BFFFBBFRRR")
+        );
+        assert!(SeatLayout::parse("7x3:FB:BR").is_err());
+        assert!(SeatLayout::parse("4294967295x1:FB:LR").is_err());
+    }
+
+    #[test]
+    fn part2() {
+        let layout = SeatLayout::STANDARD;
+        let mut ids: Vec<u32> = (40..=90).filter(|&id| id != 57).collect();
+        ids.reverse();
+        ids.swap(3, 30);
+        assert_eq!(Some(57), find_single_gap(&ids));
+        assert_eq!(Ok(57), super::part2(&layout, &ids));
+
+        // Gaps at the ends or more than one gap aren't a single missing seat.
+        assert_eq!(None, find_single_gap(&(40..=90).collect::<Vec<u32>>()));
+        let two_gaps: Vec<u32> = (40..=90).filter(|&id| id != 57 && id != 60).collect();
+        assert_eq!(None, find_single_gap(&two_gaps));
+        assert_eq!(
+            Err(SeatError::Ambiguous(vec![57, 60])),
+            super::part2(&layout, &two_gaps)
+        );
+        let duplicate: Vec<u32> = (40..=90).map(|id| if id == 57 { 58 } else { id }).collect();
+        assert_eq!(None, find_single_gap(&duplicate));
+        // Gaps at 1 and 6 with seat 2 twice add up the same as a single gap at 5.
+        assert_eq!(None, find_single_gap(&[0, 2, 2, 3, 4, 5, 7]));
+        // Duplicates that push the sum past the full range's total.
+        assert_eq!(None, find_single_gap(&[0, 4, 4, 4]));
+        assert_eq!(
+            Err(SeatError::NoEmptySeat),
+            super::part2(&layout, &[0, 4, 4, 4])
+        );
+        assert_eq!(
+            Err(SeatError::Ambiguous(vec![1, 6])),
+            super::part2(&layout, &[0, 2, 2, 3, 4, 5, 7])
+        );
+        assert_eq!(Ok(57), super::part2(&layout, &duplicate));
+        assert_eq!(Err(SeatError::NoPasses), super::part2(&layout, &[]));
+        assert_eq!(
+            Err(SeatError::NoEmptySeat),
+            super::part2(&layout, &(40..=90).collect::<Vec<u32>>())
+        );
+    }
+
+    #[test]
+    fn analyze_seats() {
+        let layout = SeatLayout::parse("3x2:FB:LR").unwrap();
+        // rows 0 and 7 are empty, seat 9 is free and 13..=15 are a larger gap
+        let ids = vec![
+            4, 5, 6, 7, 8, 10, 11, 12, 16, 17, 17, 18, 19, 20, 21, 22, 23, 5,
+        ];
+        let report = super::analyze_seats(&layout, &ids).unwrap();
+        assert_eq!(vec![9], report.candidates);
+        assert_eq!(0..1, report.front_rows);
+        assert_eq!(6..8, report.back_rows);
+        assert_eq!(vec![5, 17], report.duplicates);
+        assert_eq!(
+            Err(SeatError::OutOfRange(32)),
+            super::analyze_seats(&layout, &[4, 32])
+        );
+    }
+
+    #[test]
+    fn seat_map() {
+        let layout = SeatLayout::parse("3x2:FB:LR").unwrap();
+        let ids = vec![5, 6, 7, 8, 10, 11, 12];
+        let map = SeatMap::new(&layout, &ids, Some(9)).with_aisles(vec![1, 3]);
+        assert_eq!(
+            "   A BC D
+1  . ## #
+2  # *# #
+3  # .. .
+",
+            map.to_text()
+        );
+        let svg = map.to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(12, svg.matches("<rect x=").count());
+        assert_eq!(1, svg.matches("#e8a317").count());
+    }
+
+    #[test]
+    fn allocator() {
+        let layout = SeatLayout::parse("2x3:FB:LR").unwrap();
+        let requests = parse_requests(
+            "ann 1 window
+bob 2 aisle
+cat 3
+dan 2 window
+eve 4",
+        )
+        .unwrap();
+        // 8 columns split 3-2-3, seat (0, 0) already taken
+        let mut allocator = SeatAllocator::new(&layout, 0..4, vec![3, 5]);
+        allocator.occupy(0);
+        let assigned: Vec<Option<(Vec<String>, bool)>> = requests
+            .iter()
+            .map(|r| allocator.assign(r).map(|a| (a.codes, a.preference_met)))
+            .collect();
+        assert_eq!(
+            vec![
+                Some((vec!["FFRRR".to_string()], true)),
+                Some((vec!["FFLLR".to_string(), "FFLRL".to_string()], true)),
+                Some((
+                    vec![
+                        "FBLLL".to_string(),
+                        "FBLLR".to_string(),
+                        "FBLRL".to_string()
+                    ],
+                    true
+                )),
+                Some((vec!["FBRRL".to_string(), "FBRRR".to_string()], true)),
+                None,
+            ],
+            assigned
+        );
+        // With both window seats of the only row taken, the party still gets seated.
+        let mut allocator = SeatAllocator::new(&layout, 0..1, vec![3, 5]);
+        allocator.occupy(0);
+        allocator.occupy(7);
+        let fallback = allocator.assign(&requests[3]).unwrap();
+        assert_eq!(vec!["FFLLR", "FFLRL"], fallback.codes);
+        assert!(!fallback.preference_met);
+        for code in assigned.iter().flatten().flat_map(|(codes, _)| codes) {
+            assert!(layout.decode(code).is_ok());
         }
+        assert!(parse_requests("ann 0").is_err());
+        assert!(parse_requests("ann 1 middle").is_err());
     }
 }
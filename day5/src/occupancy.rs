@@ -0,0 +1,94 @@
+use crate::layout::SeatLayout;
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeatError {
+    NoPasses,
+    OutOfRange(u32),
+    NoEmptySeat,
+    Ambiguous(Vec<u32>),
+}
+
+impl fmt::Display for SeatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeatError::NoPasses => write!(f, "no boarding passes"),
+            SeatError::OutOfRange(id) => write!(f, "seat ID {} is outside the layout", id),
+            SeatError::NoEmptySeat => write!(f, "no empty seat between two occupied seats"),
+            SeatError::Ambiguous(ids) => write!(f, "several candidate seats: {:?}", ids),
+        }
+    }
+}
+
+// Rows in front of the first occupied row and behind the last one are treated as not
+// existing on this aircraft, so they never produce candidates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeatReport {
+    pub candidates: Vec<u32>,
+    pub front_rows: Range<u32>,
+    pub back_rows: Range<u32>,
+    pub duplicates: Vec<u32>,
+}
+
+pub fn analyze_seats(layout: &SeatLayout, ids: &[u32]) -> Result<SeatReport, SeatError> {
+    let seat_count = layout.rows() * layout.columns();
+    if let Some(&id) = ids.iter().find(|&&id| id >= seat_count) {
+        return Err(SeatError::OutOfRange(id));
+    }
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+    let mut duplicates: Vec<u32> = ids
+        .windows(2)
+        .filter(|w| w[0] == w[1])
+        .map(|w| w[0])
+        .collect();
+    duplicates.dedup();
+    ids.dedup();
+
+    let (first, last) = match (ids.first(), ids.last()) {
+        (Some(&first), Some(&last)) => (first, last),
+        _ => return Err(SeatError::NoPasses),
+    };
+    let candidates = ids
+        .windows(2)
+        .filter(|w| w[1] - w[0] == 2)
+        .map(|w| w[0] + 1)
+        .collect();
+    Ok(SeatReport {
+        candidates,
+        front_rows: 0..layout.position(first).row,
+        back_rows: layout.position(last).row + 1..layout.rows(),
+        duplicates,
+    })
+}
+
+// Finds the single ID missing from an otherwise contiguous range of IDs, without sorting.
+// The count and range say whether there can be exactly one gap. A presence bitmap over the
+// range then rules out duplicates, which could hide a second gap, and only after that does
+// the sum say which ID is missing.
+pub fn find_single_gap(ids: &[u32]) -> Option<u32> {
+    let (mut min, mut max) = (u64::MAX, 0u64);
+    let mut sum = 0u64;
+    for &id in ids {
+        let id = id as u64;
+        min = min.min(id);
+        max = max.max(id);
+        sum += id;
+    }
+    if ids.len() < 2 || max - min != ids.len() as u64 {
+        return None;
+    }
+    let mut seen = vec![0u64; ((max - min) / 64 + 1) as usize];
+    for &id in ids {
+        let offset = id as u64 - min;
+        let (word, bit) = ((offset / 64) as usize, 1u64 << (offset % 64));
+        if seen[word] & bit != 0 {
+            return None;
+        }
+        seen[word] |= bit;
+    }
+    // Every ID in the range but one is present exactly once, so this can't underflow.
+    let gap = (min + max) * (max - min + 1) / 2 - sum;
+    Some(gap as u32)
+}
@@ -0,0 +1,169 @@
+use crate::layout::{SeatLayout, SeatPosition};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeatState {
+    Empty,
+    Occupied,
+    Mine,
+}
+
+// Rows outside the span of boarding passes don't exist on the aircraft and aren't drawn.
+pub struct SeatMap<'a> {
+    layout: &'a SeatLayout,
+    aisles: Vec<u32>,
+    rows: Range<u32>,
+    occupied: HashSet<u32>,
+    mine: Option<u32>,
+}
+
+const SEAT_SIZE: u32 = 20;
+const SEAT_GAP: u32 = 4;
+const AISLE_WIDTH: u32 = 16;
+const LABEL_WIDTH: u32 = 40;
+
+impl<'a> SeatMap<'a> {
+    pub fn new(layout: &'a SeatLayout, ids: &[u32], mine: Option<u32>) -> Self {
+        let rows = match (ids.iter().min(), ids.iter().max()) {
+            (Some(&first), Some(&last)) => {
+                layout.position(first).row..layout.position(last).row + 1
+            }
+            _ => 0..layout.rows(),
+        };
+        Self {
+            layout,
+            aisles: layout.default_aisles(),
+            rows,
+            occupied: ids.iter().copied().collect(),
+            mine,
+        }
+    }
+
+    // Columns before which an aisle is drawn, e.g. `[3, 5]` for a 3-2-3 cabin.
+    pub fn with_aisles(mut self, aisles: Vec<u32>) -> Self {
+        self.aisles = aisles;
+        self
+    }
+
+    pub fn state(&self, seat: &SeatPosition) -> SeatState {
+        let id = self.layout.id(seat);
+        if Some(id) == self.mine {
+            SeatState::Mine
+        } else if self.occupied.contains(&id) {
+            SeatState::Occupied
+        } else {
+            SeatState::Empty
+        }
+    }
+
+    fn is_aisle(&self, column: u32) -> bool {
+        column > 0 && self.aisles.contains(&column)
+    }
+
+    fn column_label(column: u32) -> String {
+        match std::char::from_u32('A' as u32 + column) {
+            Some(c) if column < 26 => c.to_string(),
+            _ => column.to_string(),
+        }
+    }
+
+    // `#` occupied, `.` empty, `*` your seat, one column of spaces per aisle.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let label_width = (self.rows.end.max(1) - 1).to_string().len();
+        let single_char_labels = self.layout.columns() <= 26;
+
+        if single_char_labels {
+            out.push_str(&" ".repeat(label_width + 2));
+            for column in 0..self.layout.columns() {
+                if self.is_aisle(column) {
+                    out.push(' ');
+                }
+                out.push_str(&Self::column_label(column));
+            }
+            out.push('\n');
+        }
+        for row in self.rows.clone() {
+            write!(out, "{:>width$}  ", row, width = label_width).unwrap();
+            for column in 0..self.layout.columns() {
+                if self.is_aisle(column) {
+                    out.push(' ');
+                }
+                out.push(match self.state(&SeatPosition { row, column }) {
+                    SeatState::Empty => '.',
+                    SeatState::Occupied => '#',
+                    SeatState::Mine => '*',
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn to_svg(&self) -> String {
+        let columns = self.layout.columns();
+        let aisle_count = (0..columns).filter(|&c| self.is_aisle(c)).count() as u32;
+        let width = LABEL_WIDTH + columns * (SEAT_SIZE + SEAT_GAP) + aisle_count * AISLE_WIDTH;
+        let height = (self.rows.len() as u32 + 1) * (SEAT_SIZE + SEAT_GAP) + SEAT_GAP;
+        let x_of = |column: u32| {
+            let aisles_before = (0..=column).filter(|&c| self.is_aisle(c)).count() as u32;
+            LABEL_WIDTH + column * (SEAT_SIZE + SEAT_GAP) + aisles_before * AISLE_WIDTH
+        };
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="11">"#,
+            w = width,
+            h = height
+        )
+        .unwrap();
+        writeln!(
+            out,
+            r##"<rect width="{}" height="{}" fill="#f4f4f0"/>"##,
+            width, height
+        )
+        .unwrap();
+        for column in 0..columns {
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+                x_of(column) + SEAT_SIZE / 2,
+                SEAT_SIZE - 4,
+                Self::column_label(column)
+            )
+            .unwrap();
+        }
+        for (idx, row) in self.rows.clone().enumerate() {
+            let y = (idx as u32 + 1) * (SEAT_SIZE + SEAT_GAP);
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#,
+                LABEL_WIDTH - 8,
+                y + SEAT_SIZE - 6,
+                row
+            )
+            .unwrap();
+            for column in 0..columns {
+                let fill = match self.state(&SeatPosition { row, column }) {
+                    SeatState::Empty => "#ffffff",
+                    SeatState::Occupied => "#5b7fa6",
+                    SeatState::Mine => "#e8a317",
+                };
+                writeln!(
+                    out,
+                    r##"<rect x="{}" y="{}" width="{s}" height="{s}" rx="3" fill="{}" stroke="#333"/>"##,
+                    x_of(column),
+                    y,
+                    fill,
+                    s = SEAT_SIZE
+                )
+                .unwrap();
+            }
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeSet;
+use std::iter::FromIterator;
+
+// Answers `a`-`z` are kept as bits of a `u32`; anything else switches the set over to a
+// general `BTreeSet`. Sets go back to the bitmask form whenever they only hold `a`-`z`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnswerSet {
+    Letters(u32),
+    Other(BTreeSet<char>),
+}
+
+fn letter_bit(c: char) -> Option<u32> {
+    if c.is_ascii_lowercase() {
+        Some(1 << (c as u32 - 'a' as u32))
+    } else {
+        None
+    }
+}
+
+impl AnswerSet {
+    pub fn new() -> Self {
+        AnswerSet::Letters(0)
+    }
+
+    fn to_set(&self) -> BTreeSet<char> {
+        self.iter().collect()
+    }
+
+    fn from_set(set: BTreeSet<char>) -> Self {
+        let mut bits = 0;
+        for &c in set.iter() {
+            match letter_bit(c) {
+                Some(bit) => bits |= bit,
+                None => return AnswerSet::Other(set),
+            }
+        }
+        AnswerSet::Letters(bits)
+    }
+
+    pub fn insert(&mut self, c: char) {
+        match (self, letter_bit(c)) {
+            (AnswerSet::Letters(bits), Some(bit)) => *bits |= bit,
+            (AnswerSet::Other(set), _) => {
+                set.insert(c);
+            }
+            (this, None) => {
+                let mut set = this.to_set();
+                set.insert(c);
+                *this = AnswerSet::Other(set);
+            }
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        match (self, other) {
+            (AnswerSet::Letters(a), AnswerSet::Letters(b)) => AnswerSet::Letters(a | b),
+            _ => Self::from_set(self.to_set().union(&other.to_set()).copied().collect()),
+        }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        match (self, other) {
+            (AnswerSet::Letters(a), AnswerSet::Letters(b)) => AnswerSet::Letters(a & b),
+            _ => Self::from_set(
+                self.to_set()
+                    .intersection(&other.to_set())
+                    .copied()
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            AnswerSet::Letters(bits) => bits.count_ones() as usize,
+            AnswerSet::Other(set) => set.len(),
+        }
+    }
+
+    pub fn iter(&self) -> Box<dyn Iterator<Item = char> + '_> {
+        match self {
+            AnswerSet::Letters(bits) => {
+                let bits = *bits;
+                Box::new(
+                    ('a'..='z')
+                        .enumerate()
+                        .filter(move |(i, _)| bits & (1 << i) != 0)
+                        .map(|(_, c)| c),
+                )
+            }
+            AnswerSet::Other(set) => Box::new(set.iter().copied()),
+        }
+    }
+}
+
+impl Default for AnswerSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromIterator<char> for AnswerSet {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut set = AnswerSet::new();
+        for c in iter {
+            set.insert(c);
+        }
+        set
+    }
+}
@@ -0,0 +1,160 @@
+use crate::answer_set::AnswerSet;
+use crate::GroupAnswers;
+use std::collections::BTreeSet;
+use std::fmt;
+
+// Characters a member may give as answers. Parsed from ranges and single characters,
+// e.g. `a-z` or `a-z0-9?`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alphabet(BTreeSet<char>);
+
+impl Alphabet {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let chars: Vec<char> = s.chars().collect();
+        let mut allowed = BTreeSet::new();
+        let mut i = 0;
+        while i < chars.len() {
+            if i + 2 < chars.len() && chars[i + 1] == '-' {
+                let (from, to) = (chars[i], chars[i + 2]);
+                if from > to {
+                    return Err(format!("invalid range {}-{} in alphabet {:?}", from, to, s));
+                }
+                allowed.extend(from..=to);
+                i += 3;
+            } else {
+                allowed.insert(chars[i]);
+                i += 1;
+            }
+        }
+        if allowed.iter().any(|c| c.is_whitespace()) {
+            return Err(format!("alphabet {:?} contains whitespace", s));
+        }
+        if allowed.is_empty() {
+            return Err("alphabet is empty".to_string());
+        }
+        Ok(Alphabet(allowed))
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.0.contains(&c)
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Alphabet(('a'..='z').collect())
+    }
+}
+
+// What to do with a group that has no members, i.e. a run of several blank lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmptyGroups {
+    Reject,
+    Skip,
+    Keep,
+}
+
+impl EmptyGroups {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "reject" => Ok(EmptyGroups::Reject),
+            "skip" => Ok(EmptyGroups::Skip),
+            "keep" => Ok(EmptyGroups::Keep),
+            _ => Err(format!("expected reject, skip or keep, got {:?}", s)),
+        }
+    }
+}
+
+// Groups, lines and columns are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    InvalidAnswer {
+        group: usize,
+        line: usize,
+        column: usize,
+        answer: char,
+    },
+    EmptyGroup {
+        group: usize,
+        line: usize,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::InvalidAnswer {
+                group,
+                line,
+                column,
+                answer,
+            } => write!(
+                f,
+                "group {}, line {}, column {}: {:?} is not in the alphabet",
+                group, line, column, answer
+            ),
+            InputError::EmptyGroup { group, line } => {
+                write!(f, "group {}, line {}: group has no members", group, line)
+            }
+        }
+    }
+}
+
+// Groups are separated by a blank line. Every extra blank line in a row starts a
+// group with no members. Blank lines at the start and end of the input are ignored.
+pub fn parse_groups(
+    text: &str,
+    alphabet: &Alphabet,
+    empty_groups: EmptyGroups,
+) -> Result<Vec<GroupAnswers>, InputError> {
+    // `lines` only strips the line ending, so stray whitespace is reported like any
+    // other answer outside the alphabet.
+    let lines: Vec<&str> = text.lines().collect();
+    let first = lines.iter().position(|line| !line.is_empty());
+    let last = lines.iter().rposition(|line| !line.is_empty());
+    let (first, last) = match (first, last) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Ok(Vec::new()),
+    };
+
+    let mut groups: Vec<GroupAnswers> = Vec::new();
+    let mut current: GroupAnswers = Vec::new();
+    let mut blank_run = 0;
+    for (idx, line) in lines.iter().enumerate().take(last + 1).skip(first) {
+        let line_no = idx + 1;
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run == 1 {
+                groups.push(std::mem::take(&mut current));
+                continue;
+            }
+            match empty_groups {
+                EmptyGroups::Reject => {
+                    return Err(InputError::EmptyGroup {
+                        group: groups.len() + 1,
+                        line: line_no,
+                    })
+                }
+                EmptyGroups::Skip => {}
+                EmptyGroups::Keep => groups.push(Vec::new()),
+            }
+            continue;
+        }
+        blank_run = 0;
+        let mut member = AnswerSet::new();
+        for (col, c) in line.chars().enumerate() {
+            if !alphabet.contains(c) {
+                return Err(InputError::InvalidAnswer {
+                    group: groups.len() + 1,
+                    line: line_no,
+                    column: col + 1,
+                    answer: c,
+                });
+            }
+            member.insert(c);
+        }
+        current.push(member);
+    }
+    groups.push(current);
+    Ok(groups)
+}
@@ -1,57 +1,94 @@
-use std::collections::HashSet;
+mod answer_set;
+mod input;
+mod quorum;
+mod stats;
 
-type AnswerSet = HashSet<char>;
+use answer_set::AnswerSet;
+use input::{parse_groups, Alphabet, EmptyGroups};
+use prelude::arg_value;
+use quorum::{count_answered, Quorum};
+use stats::Report;
 
 type GroupAnswers = Vec<AnswerSet>;
 
-fn parse_input(text: &str) -> Vec<GroupAnswers> {
-    text.trim()
-        .split("\n\n")
-        .map(|group| {
-            group
-                .replace("\r", "")
-                .split("\n")
-                .map(|line| line.chars().collect::<AnswerSet>())
-                .collect::<GroupAnswers>()
-        })
-        .collect::<Vec<GroupAnswers>>()
-}
-
 fn main() {
-    let group_answers = parse_input(&prelude::read_input("input.txt"));
+    let args: Vec<String> = std::env::args().collect();
+    let alphabet = match arg_value(&args, "--alphabet") {
+        Some(alphabet) => Alphabet::parse(alphabet).unwrap(),
+        None => Alphabet::default(),
+    };
+    let empty_groups = match arg_value(&args, "--empty-groups") {
+        Some(policy) => EmptyGroups::parse(policy).unwrap(),
+        None => EmptyGroups::Reject,
+    };
+    let group_answers =
+        match parse_groups(&prelude::read_input("input.txt"), &alphabet, empty_groups) {
+            Ok(groups) => groups,
+            Err(err) => {
+                println!("Invalid input: {}", err);
+                return;
+            }
+        };
     println!();
     println!("Part 1");
     println!("Answer: {}", part1(&group_answers));
     println!("=======================");
     println!("Part 2");
     println!("Answer: {}", part2(&group_answers));
+
+    if let Some(quorum) = arg_value(&args, "--quorum") {
+        let quorum = Quorum::parse(quorum).unwrap();
+        println!("=======================");
+        println!("Quorum {:?}", quorum);
+        println!("Answer: {}", quorum_total(&group_answers, quorum));
+    }
+
+    let questions_csv = arg_value(&args, "--questions-csv");
+    let sizes_csv = arg_value(&args, "--sizes-csv");
+    if args.iter().any(|a| a == "--report") || questions_csv.is_some() || sizes_csv.is_some() {
+        let report = Report::new(&group_answers);
+        println!("=======================");
+        println!("Question  Groups  Individuals");
+        for q in report.questions.iter() {
+            println!("{:>8}  {:>6}  {:>11}", q.question, q.groups, q.individuals);
+        }
+        if let (Some(most), Some(least)) = (report.most_answered(), report.least_answered()) {
+            println!("Most answered: {} ({})", most.question, most.individuals);
+            println!("Least answered: {} ({})", least.question, least.individuals);
+        }
+        println!("Group size  Groups");
+        for (size, count) in report.group_sizes.iter() {
+            println!("{:>10}  {:>6}", size, count);
+        }
+        if let Some(path) = questions_csv {
+            std::fs::write(path, report.questions_csv()).unwrap();
+        }
+        if let Some(path) = sizes_csv {
+            std::fs::write(path, report.group_sizes_csv()).unwrap();
+        }
+    }
 }
 
-fn part1(answers: &Vec<GroupAnswers>) -> usize {
-    answers
-        .iter()
-        .map(|x| x.iter().flatten().copied().collect::<HashSet<char>>().len())
-        .sum()
+fn part1(answers: &[GroupAnswers]) -> usize {
+    quorum_total(answers, Quorum::ANYONE)
+}
+
+fn part2(answers: &[GroupAnswers]) -> usize {
+    quorum_total(answers, Quorum::EVERYONE)
 }
 
-fn part2(answers: &Vec<GroupAnswers>) -> usize {
+fn quorum_total(answers: &[GroupAnswers], quorum: Quorum) -> usize {
     answers
         .iter()
-        .map(|group| {
-            group[1..]
-                .iter()
-                .fold(group[0].clone(), |c, n| {
-                    c.intersection(n).copied().collect()
-                })
-                .len()
-        })
+        .map(|group| count_answered(group, quorum))
         .sum()
 }
 
 #[cfg(test)]
 mod tests {
     fn get_inputs() -> Vec<super::GroupAnswers> {
-        super::parse_input(
+        use super::{parse_groups, Alphabet, EmptyGroups};
+        parse_groups(
             "abc
 
 a
@@ -68,7 +105,10 @@ a
 
 b
 ",
+            &Alphabet::default(),
+            EmptyGroups::Reject,
         )
+        .unwrap()
     }
     #[test]
     fn test_part1() {
@@ -81,4 +121,123 @@ b
         let group_answers = get_inputs();
         assert_eq!(6, super::part2(&group_answers));
     }
+
+    #[test]
+    fn test_answer_set() {
+        use super::AnswerSet;
+        let abc: AnswerSet = "abc".chars().collect();
+        let bcd: AnswerSet = "bcd".chars().collect();
+        assert_eq!(AnswerSet::Letters(0b110), abc.intersection(&bcd));
+        assert_eq!(4, abc.union(&bcd).len());
+
+        let mixed: AnswerSet = "bZ?".chars().collect();
+        assert!(matches!(mixed, AnswerSet::Other(_)));
+        assert_eq!(
+            vec!['?', 'Z', 'a', 'b', 'c'],
+            abc.union(&mixed).iter().collect::<Vec<char>>()
+        );
+        // The only shared answer is a letter, so the result is a bitmask again.
+        assert_eq!(AnswerSet::Letters(0b10), abc.intersection(&mixed));
+    }
+
+    #[test]
+    fn test_quorum() {
+        use super::Quorum;
+        let group_answers = get_inputs();
+        assert_eq!(11, super::quorum_total(&group_answers, Quorum::ANYONE));
+        assert_eq!(6, super::quorum_total(&group_answers, Quorum::EVERYONE));
+        // "ab"/"ac" has `a` twice; "a" x4 has `a` four times
+        assert_eq!(2, super::quorum_total(&group_answers, Quorum::AtLeast(2)));
+        assert_eq!(
+            8,
+            super::quorum_total(&group_answers, Quorum::parse("50%").unwrap())
+        );
+        assert_eq!(2, Quorum::parse("2/3").unwrap().required(3));
+        assert_eq!(1, Quorum::parse("0").unwrap().required(3));
+        assert!(Quorum::parse("3/2").is_err());
+        assert!(Quorum::parse("1/0").is_err());
+        let huge = Quorum::parse("18446744073709551615/18446744073709551615").unwrap();
+        assert_eq!(3, huge.required(3));
+    }
+
+    #[test]
+    fn test_report() {
+        let report = super::Report::new(&get_inputs());
+        assert_eq!(
+            "question,groups,individuals
+a,4,8
+b,4,4
+c,3,3
+",
+            report.questions_csv()
+        );
+        assert_eq!('a', report.most_answered().unwrap().question);
+        assert_eq!('c', report.least_answered().unwrap().question);
+        assert_eq!(
+            "group_size,groups
+1,2
+2,1
+3,1
+4,1
+",
+            report.group_sizes_csv()
+        );
+        let alphabet = super::Alphabet::parse("a,\"").unwrap();
+        let odd = super::parse_groups("a,\"\n,", &alphabet, super::EmptyGroups::Reject);
+        let odd = super::Report::new(&odd.unwrap());
+        assert_eq!(
+            "question,groups,individuals\n\"\"\"\",1,1\n\",\",1,2\na,1,1\n",
+            odd.questions_csv()
+        );
+    }
+
+    #[test]
+    fn test_input_errors() {
+        use super::input::InputError;
+        use super::{parse_groups, Alphabet, EmptyGroups};
+        let alphabet = Alphabet::default();
+        assert_eq!(
+            Err(InputError::InvalidAnswer {
+                group: 2,
+                line: 4,
+                column: 2,
+                answer: ' '
+            }),
+            parse_groups("ab\n\nc\nd e\n", &alphabet, EmptyGroups::Reject)
+        );
+        assert!(parse_groups(
+            "a1",
+            &Alphabet::parse("a-z0-9").unwrap(),
+            EmptyGroups::Reject
+        )
+        .is_ok());
+
+        let text = "\nab\n\n\n\nc\n\n";
+        assert_eq!(
+            Err(InputError::EmptyGroup { group: 2, line: 4 }),
+            parse_groups(text, &alphabet, EmptyGroups::Reject)
+        );
+        let skipped = parse_groups(text, &alphabet, EmptyGroups::Skip).unwrap();
+        assert_eq!(2, skipped.len());
+        let kept = parse_groups(text, &alphabet, EmptyGroups::Keep).unwrap();
+        assert_eq!(
+            vec![1, 0, 0, 1],
+            kept.iter().map(|g| g.len()).collect::<Vec<_>>()
+        );
+        assert_eq!(0, super::part2(&kept[1..3]));
+        assert_eq!(3, super::part2(&kept));
+
+        assert!(Alphabet::parse("z-a").is_err());
+        assert!(Alphabet::parse("a b").is_err());
+
+        assert_eq!(
+            Err(InputError::InvalidAnswer {
+                group: 1,
+                line: 2,
+                column: 3,
+                answer: '\t'
+            }),
+            parse_groups("ab\r\nab\t\r\n", &alphabet, EmptyGroups::Reject)
+        );
+    }
 }
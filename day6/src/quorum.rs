@@ -0,0 +1,89 @@
+use crate::answer_set::AnswerSet;
+use std::collections::HashMap;
+
+// How many members of a group must have answered a question for it to count.
+// `Fraction(num, den)` rounds up, so `Fraction(1, 2)` of a group of 3 is 2 members.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quorum {
+    AtLeast(usize),
+    Fraction(usize, usize),
+}
+
+impl Quorum {
+    pub const ANYONE: Quorum = Quorum::AtLeast(1);
+    pub const EVERYONE: Quorum = Quorum::Fraction(1, 1);
+
+    // Accepts `k`, `num/den` or `pct%`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let err = || format!("expected a quorum like `2`, `2/3` or `50%`, got {:?}", s);
+        let quorum = if let Some(pct) = s.strip_suffix('%') {
+            Quorum::Fraction(pct.parse().map_err(|_| err())?, 100)
+        } else if let Some((num, den)) = s.split_once('/') {
+            Quorum::Fraction(
+                num.parse().map_err(|_| err())?,
+                den.parse().map_err(|_| err())?,
+            )
+        } else {
+            Quorum::AtLeast(s.parse().map_err(|_| err())?)
+        };
+        match quorum {
+            Quorum::Fraction(num, den) if den == 0 || num > den => Err(err()),
+            _ => Ok(quorum),
+        }
+    }
+
+    // Never less than one: a question nobody answered doesn't count. Fractions are worked
+    // out in `u128` so large numerators can't overflow; the result is at most `group_size`.
+    pub fn required(&self, group_size: usize) -> usize {
+        let required = match *self {
+            Quorum::AtLeast(k) => k,
+            Quorum::Fraction(num, den) => {
+                (group_size as u128 * num as u128).div_ceil(den as u128) as usize
+            }
+        };
+        required.max(1)
+    }
+}
+
+// Number of members who answered each question.
+pub fn answer_counts(group: &[AnswerSet]) -> HashMap<char, usize> {
+    let mut letters = [0usize; 26];
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for member in group {
+        match member {
+            AnswerSet::Letters(bits) => {
+                let mut bits = *bits;
+                while bits != 0 {
+                    letters[bits.trailing_zeros() as usize] += 1;
+                    bits &= bits - 1;
+                }
+            }
+            AnswerSet::Other(set) => {
+                for &c in set.iter() {
+                    *counts.entry(c).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+    for (c, &n) in ('a'..='z').zip(letters.iter()).filter(|(_, &n)| n > 0) {
+        *counts.entry(c).or_insert(0) += n;
+    }
+    counts
+}
+
+// Union and intersection are the two ends of the scale and skip the per-question counts.
+pub fn count_answered(group: &[AnswerSet], quorum: Quorum) -> usize {
+    let required = quorum.required(group.len());
+    match group.split_first() {
+        None => 0,
+        Some(_) if required == 1 => group.iter().fold(AnswerSet::new(), |c, n| c.union(n)).len(),
+        Some((first, rest)) if required == group.len() => rest
+            .iter()
+            .fold(first.clone(), |c, n| c.intersection(n))
+            .len(),
+        Some(_) => answer_counts(group)
+            .values()
+            .filter(|&&n| n >= required)
+            .count(),
+    }
+}
@@ -0,0 +1,76 @@
+use crate::quorum::answer_counts;
+use crate::GroupAnswers;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuestionStats {
+    pub question: char,
+    pub groups: usize,
+    pub individuals: usize,
+}
+
+pub struct Report {
+    pub questions: Vec<QuestionStats>,
+    pub group_sizes: BTreeMap<usize, usize>,
+}
+
+impl Report {
+    pub fn new(answers: &[GroupAnswers]) -> Self {
+        let mut questions: BTreeMap<char, QuestionStats> = BTreeMap::new();
+        let mut group_sizes: BTreeMap<usize, usize> = BTreeMap::new();
+        for group in answers {
+            *group_sizes.entry(group.len()).or_insert(0) += 1;
+            for (question, individuals) in answer_counts(group) {
+                let stats = questions.entry(question).or_insert(QuestionStats {
+                    question,
+                    groups: 0,
+                    individuals: 0,
+                });
+                stats.groups += 1;
+                stats.individuals += individuals;
+            }
+        }
+        Self {
+            questions: questions.into_values().collect(),
+            group_sizes,
+        }
+    }
+
+    // Ranked by the number of individuals; ties go to the earlier question.
+    pub fn most_answered(&self) -> Option<&QuestionStats> {
+        self.questions.iter().rev().max_by_key(|q| q.individuals)
+    }
+
+    pub fn least_answered(&self) -> Option<&QuestionStats> {
+        self.questions.iter().min_by_key(|q| q.individuals)
+    }
+
+    pub fn questions_csv(&self) -> String {
+        let mut out = String::from("question,groups,individuals\n");
+        for q in self.questions.iter() {
+            out.push_str(&format!(
+                "{},{},{}\n",
+                csv_field(&q.question.to_string()),
+                q.groups,
+                q.individuals
+            ));
+        }
+        out
+    }
+
+    pub fn group_sizes_csv(&self) -> String {
+        let mut out = String::from("group_size,groups\n");
+        for (size, count) in self.group_sizes.iter() {
+            out.push_str(&format!("{},{}\n", size, count));
+        }
+        out
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
@@ -0,0 +1,84 @@
+use crate::graph::BagGraph;
+use crate::ColorId;
+use std::collections::BTreeSet;
+
+// Which part of the graph to export: everything, or only the colours reachable from
+// (contained in) or to (containing) one colour, that colour included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+    All,
+    From(ColorId),
+    To(ColorId),
+}
+
+fn scoped_colors(graph: &BagGraph, scope: Scope) -> BTreeSet<ColorId> {
+    match scope {
+        Scope::All => graph.colors().collect(),
+        Scope::From(color) => {
+            let mut colors = graph.descendants(color);
+            colors.insert(color);
+            colors
+        }
+        Scope::To(color) => {
+            let mut colors = graph.ancestors(color);
+            colors.insert(color);
+            colors
+        }
+    }
+}
+
+// Edges with both ends inside the scope, as (container, quantity, contained).
+fn scoped_edges<'a>(
+    graph: &'a BagGraph,
+    colors: &'a BTreeSet<ColorId>,
+) -> impl Iterator<Item = (ColorId, u32, ColorId)> + 'a {
+    colors.iter().flat_map(move |&color| {
+        graph
+            .children(color)
+            .iter()
+            .filter(move |(_, child)| colors.contains(child))
+            .map(move |&(n, child)| (color, n, child))
+    })
+}
+
+pub fn to_dot(graph: &BagGraph, scope: Scope) -> String {
+    let quote = |color: ColorId| {
+        format!(
+            "\"{}\"",
+            graph.name(color).replace('\\', "\\\\").replace('"', "\\\"")
+        )
+    };
+    let colors = scoped_colors(graph, scope);
+    let mut out = String::from("digraph bags {\n");
+    for &color in colors.iter() {
+        out.push_str(&format!("    {};\n", quote(color)));
+    }
+    for (from, n, to) in scoped_edges(graph, &colors) {
+        out.push_str(&format!(
+            "    {} -> {} [label=\"{}\"];\n",
+            quote(from),
+            quote(to),
+            n
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+// Nodes are named by their colour ID since Mermaid IDs can't contain spaces.
+pub fn to_mermaid(graph: &BagGraph, scope: Scope) -> String {
+    let node = |color: ColorId| format!("c{}", color.0);
+    let colors = scoped_colors(graph, scope);
+    let mut out = String::from("graph TD\n");
+    for &color in colors.iter() {
+        out.push_str(&format!(
+            "    {}[\"{}\"]\n",
+            node(color),
+            graph.name(color).replace('"', "#quot;")
+        ));
+    }
+    for (from, n, to) in scoped_edges(graph, &colors) {
+        out.push_str(&format!("    {} -->|{}| {}\n", node(from), n, node(to)));
+    }
+    out
+}
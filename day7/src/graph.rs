@@ -0,0 +1,208 @@
+use crate::{ColorContainedIn, ColorContains, ColorId, ColorInterner};
+use prelude::InternId;
+use smallvec::SmallVec;
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    // The colours along the cycle, starting and ending with the same colour.
+    Cycle(Vec<String>),
+    Overflow(String),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::Cycle(colors) => {
+                write!(f, "bags contain themselves: {}", colors.join(" -> "))
+            }
+            GraphError::Overflow(color) => {
+                write!(f, "too many bags inside {} to count", color)
+            }
+        }
+    }
+}
+
+// Rules as a graph with an edge from each colour to the colours it contains. Both
+// directions are kept, indexed by `ColorId`. The graph is always acyclic.
+pub struct BagGraph {
+    interner: ColorInterner,
+    children: Vec<ColorContains>,
+    parents: Vec<ColorContainedIn>,
+    // Bags inside one bag of each colour, `None` where the count overflows.
+    contained: Vec<Option<u64>>,
+}
+
+impl BagGraph {
+    pub fn new(
+        interner: ColorInterner,
+        rules: Vec<(ColorId, ColorContains)>,
+    ) -> Result<Self, GraphError> {
+        let mut children: Vec<ColorContains> = vec![SmallVec::new(); interner.len()];
+        let mut parents: Vec<ColorContainedIn> = vec![SmallVec::new(); interner.len()];
+        for (color, contains) in rules {
+            for &(_, child) in contains.iter() {
+                let child_parents = &mut parents[child.index()];
+                if !child_parents.contains(&color) {
+                    child_parents.push(color);
+                }
+            }
+            children[color.index()] = contains;
+        }
+        let mut graph = Self {
+            interner,
+            children,
+            parents,
+            contained: Vec::new(),
+        };
+        let order = graph.topological_order()?;
+        graph.contained = vec![Some(0); graph.children.len()];
+        for color in order {
+            graph.contained[color.index()] =
+                graph
+                    .children(color)
+                    .iter()
+                    .try_fold(0u64, |total, &(n, child)| {
+                        let inside = graph.contained[child.index()]?.checked_add(1)?;
+                        total.checked_add(inside.checked_mul(u64::from(n))?)
+                    });
+        }
+        Ok(graph)
+    }
+
+    // Colours ordered so that every colour comes after everything it contains.
+    fn topological_order(&self) -> Result<Vec<ColorId>, GraphError> {
+        const UNSEEN: u8 = 0;
+        const ON_STACK: u8 = 1;
+        const DONE: u8 = 2;
+        let mut state = vec![UNSEEN; self.children.len()];
+        let mut order = Vec::with_capacity(self.children.len());
+        for root in self.colors() {
+            if state[root.index()] != UNSEEN {
+                continue;
+            }
+            // Each entry is a colour and the index of the next child to visit.
+            let mut stack: Vec<(ColorId, usize)> = vec![(root, 0)];
+            state[root.index()] = ON_STACK;
+            while let Some((color, next)) = stack.last_mut() {
+                let color = *color;
+                match self.children(color).get(*next) {
+                    Some(&(_, child)) => {
+                        *next += 1;
+                        match state[child.index()] {
+                            UNSEEN => {
+                                state[child.index()] = ON_STACK;
+                                stack.push((child, 0));
+                            }
+                            ON_STACK => {
+                                let start = stack.iter().position(|&(c, _)| c == child).unwrap();
+                                let cycle = stack[start..]
+                                    .iter()
+                                    .map(|&(c, _)| c)
+                                    .chain(Some(child))
+                                    .map(|c| self.name(c).to_string())
+                                    .collect();
+                                return Err(GraphError::Cycle(cycle));
+                            }
+                            _ => {}
+                        }
+                    }
+                    None => {
+                        state[color.index()] = DONE;
+                        order.push(color);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    pub fn id(&self, name: &str) -> Option<ColorId> {
+        self.interner.get(name)
+    }
+
+    pub fn name(&self, color: ColorId) -> &str {
+        self.interner.resolve(color).unwrap()
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = ColorId> + '_ {
+        self.interner.iter().map(|(color, _)| color)
+    }
+
+    pub fn children(&self, color: ColorId) -> &[(u32, ColorId)] {
+        &self.children[color.index()]
+    }
+
+    pub fn parents(&self, color: ColorId) -> &[ColorId] {
+        &self.parents[color.index()]
+    }
+
+    // Every colour that eventually contains `color`.
+    pub fn ancestors(&self, color: ColorId) -> BTreeSet<ColorId> {
+        self.reachable(color, |c| self.parents(c).to_vec())
+    }
+
+    // Every colour `color` eventually contains.
+    pub fn descendants(&self, color: ColorId) -> BTreeSet<ColorId> {
+        self.reachable(color, |c| {
+            self.children(c).iter().map(|&(_, c)| c).collect()
+        })
+    }
+
+    fn reachable<F>(&self, color: ColorId, next: F) -> BTreeSet<ColorId>
+    where
+        F: Fn(ColorId) -> Vec<ColorId>,
+    {
+        let mut seen = BTreeSet::new();
+        let mut queue: VecDeque<ColorId> = next(color).into();
+        while let Some(c) = queue.pop_front() {
+            if seen.insert(c) {
+                queue.extend(next(c));
+            }
+        }
+        seen
+    }
+
+    // Number of bags inside one bag of `color`.
+    pub fn total_contained(&self, color: ColorId) -> Result<u64, GraphError> {
+        self.contained[color.index()]
+            .ok_or_else(|| GraphError::Overflow(self.name(color).to_string()))
+    }
+
+    // Every chain of containment from `from` down to `to`, both ends included.
+    // Only colours that hold `to` are followed, so the search never wanders into parts of
+    // the graph that can't produce a path.
+    pub fn paths(&self, from: ColorId, to: ColorId) -> Vec<Vec<ColorId>> {
+        let mut leads_to = self.ancestors(to);
+        leads_to.insert(to);
+        let mut paths = Vec::new();
+        let mut path = vec![from];
+        if from != to && leads_to.contains(&from) {
+            self.extend_paths(to, &leads_to, &mut path, &mut paths);
+        }
+        paths
+    }
+
+    fn extend_paths(
+        &self,
+        to: ColorId,
+        leads_to: &BTreeSet<ColorId>,
+        path: &mut Vec<ColorId>,
+        paths: &mut Vec<Vec<ColorId>>,
+    ) {
+        let last = *path.last().unwrap();
+        if last == to {
+            paths.push(path.clone());
+            return;
+        }
+        for &(_, child) in self.children(last) {
+            if leads_to.contains(&child) {
+                path.push(child);
+                self.extend_paths(to, leads_to, path, paths);
+                path.pop();
+            }
+        }
+    }
+}
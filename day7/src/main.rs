@@ -1,159 +1,199 @@
-use nom::{
-    branch,
-    bytes::complete,
-    bytes::streaming,
-    character::complete::{digit1, space0, space1},
-    combinator, IResult,
-};
-use smallvec::{smallvec, SmallVec};
-use std::collections::{HashMap, HashSet, VecDeque};
-
-type InternId = u32;
-
-struct Interner {
-    lookup_string: Vec<String>,
-    lookup_id: HashMap<String, InternId>,
-}
+mod export;
+mod graph;
+mod parser;
+mod validate;
 
-impl Interner {
-    pub fn new() -> Self {
-        Self {
-            lookup_id: HashMap::new(),
-            lookup_string: Vec::new(),
-        }
-    }
+use export::Scope;
+use graph::{BagGraph, GraphError};
+use parser::{parse_rules, ParseError};
+use prelude::{arg_value, InternId, Interner};
+use smallvec::SmallVec;
+use std::convert::TryFrom;
+use std::fmt;
+use validate::{validate, Issue, Rule};
 
-    pub fn intern<S: Into<String>>(&mut self, s: S) -> u32 {
-        let text: String = s.into();
-        if let Some(&id) = self.lookup_id.get(&text) {
-            id
-        } else {
-            let id = self.lookup_string.len() as u32;
-            self.lookup_string.push(text.clone());
-            self.lookup_id.insert(text, id);
-            id
-        }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct ColorId(u32);
+
+impl InternId for ColorId {
+    fn from_index(index: usize) -> Self {
+        ColorId(u32::try_from(index).expect("too many colors to intern"))
     }
 
-    #[allow(dead_code)]
-    pub fn get(&self, id: u32) -> Option<&str> {
-        self.lookup_string.get(id as usize).map(|s| s as &str)
+    fn index(self) -> usize {
+        self.0 as usize
     }
 }
 
-type ColorContains = SmallVec<[(u8, InternId); 4]>;
-type ColorContainedIn = SmallVec<[InternId; 4]>;
+type ColorInterner = Interner<ColorId>;
 
-fn parse_rule<'a>(
-    interner: &mut Interner,
-    input: &'a str,
-) -> IResult<&'a str, (InternId, ColorContains)> {
-    const COLOR_AND_ARGS_SEP: &'static str = " bags contain ";
-    let (input, color) = streaming::take_until(COLOR_AND_ARGS_SEP)(input)?;
-    let input = &input[COLOR_AND_ARGS_SEP.len()..];
-    let color = interner.intern(color);
+type ColorContains = SmallVec<[(u32, ColorId); 4]>;
+type ColorContainedIn = SmallVec<[ColorId; 4]>;
 
-    let (input, rules) = parse_rule_list(interner, input)?;
-
-    Ok((input, (color, rules)))
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LoadError {
+    Parse(ParseError),
+    Invalid(Vec<Issue>),
+    Graph(GraphError),
 }
 
-fn parse_rule_list<'a>(interner: &mut Interner, input: &'a str) -> IResult<&'a str, ColorContains> {
-    let (input, args) = complete::take_till(|c| c == '.')(input)?;
-    if args == "no other bags" {
-        return Ok((input, SmallVec::new()));
-    }
-    let mut colors: ColorContains = SmallVec::new();
-    let mut args: &str = space0(args)?.0;
-    while args.len() > 0 {
-        let (next_args, arg) = complete::take_till(|c| c == ',')(args)?;
-        args = if next_args.len() > 1 {
-            &next_args[2..]
-        } else {
-            ""
-        };
-        let (arg, n) = combinator::map_res(digit1, |n: &str| n.parse::<u8>())(arg)?;
-        let (color, _) = space1(arg)?;
-        let color: &str = (branch::alt((
-            combinator::peek(streaming::take_until(" bag")),
-            streaming::take_until(" bags"),
-        )))(color)?
-        .1;
-        colors.push((n, interner.intern(color)));
-    }
-    Ok((input, colors))
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Parse(err) => write!(f, "{}", err),
+            LoadError::Invalid(issues) => {
+                let issues: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+                write!(f, "{}", issues.join("\n"))
+            }
+            LoadError::Graph(err) => write!(f, "{}", err),
+        }
+    }
 }
 
-fn part1(contained_in: &HashMap<InternId, ColorContainedIn>, shiny_gold: InternId) -> usize {
-    let mut contain_shiny_gold = HashSet::new();
-    let mut queue = VecDeque::new();
-    for &bt in contained_in.get(&shiny_gold).into_iter().flatten() {
-        queue.push_back(bt);
+// Rules are validated before the graph is built. Problems that don't stop the rules
+// from being solved, like colours unrelated to `root`, are returned alongside the graph.
+fn load(text: &str, root: Option<&str>) -> Result<(BagGraph, Vec<Issue>), LoadError> {
+    let mut interner = ColorInterner::new();
+    let rules: Vec<Rule> = parse_rules(text)
+        .map_err(LoadError::Parse)?
+        .into_iter()
+        .map(|rule| Rule {
+            line: rule.line,
+            color: interner.intern(&rule.color),
+            contains: rule
+                .contains
+                .iter()
+                .map(|(n, color)| (*n, interner.intern(color)))
+                .collect(),
+        })
+        .collect();
+    let root = root.and_then(|name| interner.get(name));
+    let (errors, warnings): (Vec<Issue>, Vec<Issue>) = validate(&interner, &rules, root)
+        .into_iter()
+        .partition(Issue::is_error);
+    if !errors.is_empty() {
+        return Err(LoadError::Invalid(errors));
     }
+    let rules = rules
+        .into_iter()
+        .map(|rule| (rule.color, rule.contains))
+        .collect();
+    let graph = BagGraph::new(interner, rules).map_err(LoadError::Graph)?;
+    Ok((graph, warnings))
+}
 
-    while let Some(bag_type) = queue.pop_front() {
-        contain_shiny_gold.insert(bag_type);
-        for &bt in contained_in.get(&bag_type).into_iter().flatten() {
-            queue.push_back(bt);
-        }
-    }
-    contain_shiny_gold.len()
+fn part1(graph: &BagGraph, color: ColorId) -> usize {
+    graph.ancestors(color).len()
 }
 
-fn part2(contains: &HashMap<InternId, ColorContains>, color: InternId) -> usize {
-    contains
-        .get(&color)
-        .into_iter()
-        .flatten()
-        .map(|(n, sub_color)| (*n as usize) * (1 + part2(contains, *sub_color)))
-        .sum()
+fn part2(graph: &BagGraph, color: ColorId) -> Result<u64, GraphError> {
+    graph.total_contained(color)
 }
 
-fn contained_in(
-    contains: &HashMap<InternId, ColorContains>,
-) -> HashMap<InternId, ColorContainedIn> {
-    let mut contained_in: HashMap<InternId, ColorContainedIn> = HashMap::new();
-    for (color1, color2) in contains
-        .iter()
-        .flat_map(|(&color, contained)| contained.iter().map(move |(_, color2)| (*color2, color)))
-    {
-        if let Some(xs) = contained_in.get_mut(&color1) {
-            if !xs.contains(&color2) {
-                xs.push(color2);
-            }
-        } else {
-            contained_in.insert(color1, smallvec![color2]);
+fn main() {
+    prelude::init_verbosity();
+    let args: Vec<String> = std::env::args().collect();
+    let name = arg_value(&args, "--color").unwrap_or("shiny gold");
+    let text = prelude::read_input("input.txt");
+    let (graph, warnings) = match load(&text, Some(name)) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            println!("Invalid rules:\n{}", err);
+            return;
         }
+    };
+    if !warnings.is_empty() {
+        println!("{} warnings (use -v to list them)", warnings.len());
+    }
+    for warning in warnings.iter() {
+        prelude::verbose!("{}", warning);
+    }
+    prelude::verbose!("{} colors", graph.colors().count());
+    if let Err(err) = export_graph(&graph, &args) {
+        println!("Export failed: {}", err);
     }
-    contained_in
-}
 
-fn main() {
-    let mut interner = Interner::new();
-    let contains: HashMap<InternId, ColorContains> = prelude::read_input_lines("input.txt")
-        .iter()
-        .map(|line| parse_rule(&mut interner, line).unwrap().1)
-        .collect();
+    let color = match graph.id(name) {
+        Some(color) => color,
+        None => {
+            println!("Unknown color {:?}", name);
+            return;
+        }
+    };
+    prelude::verbose!(
+        "{:?} is directly inside: {:?}",
+        name,
+        graph
+            .parents(color)
+            .iter()
+            .map(|&c| graph.name(c))
+            .collect::<Vec<_>>()
+    );
+    prelude::verbose!(
+        "{:?} directly contains: {:?}",
+        name,
+        graph
+            .children(color)
+            .iter()
+            .map(|&(n, c)| format!("{} {}", n, graph.name(c)))
+            .collect::<Vec<_>>()
+    );
+    prelude::verbose!(
+        "{:?} holds {} distinct colors",
+        name,
+        graph.descendants(color).len()
+    );
 
-    let shiny_gold = interner.intern("shiny gold");
     println!();
     println!("Part 1");
-    println!("Answer: {}", part1(&contained_in(&contains), shiny_gold));
+    println!("Answer: {}", part1(&graph, color));
     println!("=======================");
     println!("Part 2");
-    println!("Answer: {}", part2(&contains, shiny_gold));
+    match part2(&graph, color) {
+        Ok(count) => println!("Answer: {}", count),
+        Err(err) => println!("No answer: {}", err),
+    }
+
+    if let Some(target) = arg_value(&args, "--paths-to") {
+        let target = match graph.id(target) {
+            Some(target) => target,
+            None => {
+                println!("Unknown color {:?}", target);
+                return;
+            }
+        };
+        println!("=======================");
+        for path in graph.paths(color, target) {
+            let names: Vec<&str> = path.iter().map(|&c| graph.name(c)).collect();
+            println!("{}", names.join(" -> "));
+        }
+    }
+}
+
+fn export_graph(graph: &BagGraph, args: &[String]) -> Result<(), String> {
+    let lookup = |name: &str| {
+        graph
+            .id(name)
+            .ok_or_else(|| format!("Unknown color {:?}", name))
+    };
+    let scope = match (arg_value(args, "--from"), arg_value(args, "--to")) {
+        (Some(_), Some(_)) => return Err("use only one of --from and --to".to_string()),
+        (Some(from), None) => Scope::From(lookup(from)?),
+        (None, Some(to)) => Scope::To(lookup(to)?),
+        (None, None) => Scope::All,
+    };
+    if let Some(path) = arg_value(args, "--dot") {
+        std::fs::write(path, export::to_dot(graph, scope)).map_err(prelude::stringify_err)?;
+    }
+    if let Some(path) = arg_value(args, "--mermaid") {
+        std::fs::write(path, export::to_mermaid(graph, scope)).map_err(prelude::stringify_err)?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ColorContains, InternId, Interner};
-    use std::collections::HashMap;
-
-    #[test]
-    fn part1_test() {
-        let mut interner = Interner::new();
-        let contains: HashMap<InternId, ColorContains> =
-            "light red bags contain 1 bright white bag, 2 muted yellow bags.
+    const EXAMPLE: &str = "light red bags contain 1 bright white bag, 2 muted yellow bags.
 dark orange bags contain 3 bright white bags, 4 muted yellow bags.
 bright white bags contain 1 shiny gold bag.
 muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
@@ -161,30 +201,280 @@ shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
 dark olive bags contain 3 faded blue bags, 4 dotted black bags.
 vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
 faded blue bags contain no other bags.
-dotted black bags contain no other bags."
-                .split("\n")
-                .map(|line| super::parse_rule(&mut interner, line).unwrap().1)
-                .collect();
+dotted black bags contain no other bags.";
 
-        let shiny_gold = interner.intern("shiny gold");
-        assert_eq!(4, super::part1(&super::contained_in(&contains), shiny_gold));
+    #[test]
+    fn part1_test() {
+        let graph = super::load(EXAMPLE, None).unwrap().0;
+        let shiny_gold = graph.id("shiny gold").unwrap();
+        assert_eq!(4, super::part1(&graph, shiny_gold));
     }
 
     #[test]
     fn part2_test() {
-        let mut interner = Interner::new();
-        let contains: HashMap<InternId, ColorContains> = "shiny gold bags contain 2 dark red bags.
+        let graph = super::load(
+            "shiny gold bags contain 2 dark red bags.
 dark red bags contain 2 dark orange bags.
 dark orange bags contain 2 dark yellow bags.
 dark yellow bags contain 2 dark green bags.
 dark green bags contain 2 dark blue bags.
 dark blue bags contain 2 dark violet bags.
-dark violet bags contain no other bags."
-            .split("\n")
-            .map(|line| super::parse_rule(&mut interner, line).unwrap().1)
+dark violet bags contain no other bags.",
+            None,
+        )
+        .unwrap()
+        .0;
+        let shiny_gold = graph.id("shiny gold").unwrap();
+        assert_eq!(Ok(126), super::part2(&graph, shiny_gold));
+    }
+
+    #[test]
+    fn graph_test() {
+        let graph = super::load(EXAMPLE, None).unwrap().0;
+        let id = |name| graph.id(name).unwrap();
+        let names = |colors: Vec<super::ColorId>| {
+            let mut names: Vec<&str> = colors.into_iter().map(|c| graph.name(c)).collect();
+            names.sort_unstable();
+            names
+        };
+        assert_eq!(
+            vec!["bright white", "muted yellow"],
+            names(graph.parents(id("shiny gold")).to_vec())
+        );
+        assert_eq!(
+            vec!["dark olive", "dotted black", "faded blue", "vibrant plum"],
+            names(graph.descendants(id("shiny gold")).into_iter().collect())
+        );
+        assert_eq!(0, super::part1(&graph, id("light red")));
+        assert_eq!(Ok(7), super::part2(&graph, id("dark olive")));
+        assert_eq!(
+            vec![
+                vec![
+                    "light red",
+                    "bright white",
+                    "shiny gold",
+                    "dark olive",
+                    "faded blue"
+                ],
+                vec![
+                    "light red",
+                    "bright white",
+                    "shiny gold",
+                    "vibrant plum",
+                    "faded blue"
+                ],
+                vec!["light red", "muted yellow", "faded blue"],
+                vec![
+                    "light red",
+                    "muted yellow",
+                    "shiny gold",
+                    "dark olive",
+                    "faded blue"
+                ],
+                vec![
+                    "light red",
+                    "muted yellow",
+                    "shiny gold",
+                    "vibrant plum",
+                    "faded blue"
+                ],
+            ],
+            {
+                let mut paths: Vec<Vec<&str>> = graph
+                    .paths(id("light red"), id("faded blue"))
+                    .into_iter()
+                    .map(|p| p.into_iter().map(|c| graph.name(c)).collect())
+                    .collect();
+                paths.sort();
+                paths
+            }
+        );
+        assert!(graph.id("plaid magenta").is_none());
+        assert!(graph.paths(id("shiny gold"), id("light red")).is_empty());
+
+        // Two bags per level that both hold both bags of the next level have 2^40 routes
+        // to the bottom, none of which reach the unrelated target.
+        let mut rules: String = (0..40)
+            .map(|i| {
+                format!(
+                    "left {0} bags contain 1 left {1} bag, 1 right {1} bag.
+right {0} bags contain 1 left {1} bag, 1 right {1} bag.
+",
+                    i,
+                    i + 1
+                )
+            })
             .collect();
+        rules.push_str(
+            "left 40 bags contain no other bags.\nright 40 bags contain no other bags.\n",
+        );
+        rules.push_str("dark olive bags contain 1 right 39 bag.\n");
+        let graph = super::load(&rules, None).unwrap().0;
+        let id = |name| graph.id(name).unwrap();
+        assert!(graph.paths(id("left 0"), id("dark olive")).is_empty());
+        assert_eq!(2, graph.paths(id("left 38"), id("right 40")).len());
+    }
+
+    #[test]
+    fn cycle_test() {
+        use super::{GraphError, LoadError};
+        let cyclic = "light red bags contain 1 dark red bag.
+dark red bags contain 2 dark blue bags, 1 faded blue bag.
+dark blue bags contain 3 light red bags.
+faded blue bags contain no other bags.";
+        assert_eq!(
+            Some(LoadError::Graph(GraphError::Cycle(vec![
+                "light red".to_string(),
+                "dark red".to_string(),
+                "dark blue".to_string(),
+                "light red".to_string()
+            ]))),
+            super::load(cyclic, None).err()
+        );
 
-        let shiny_gold = interner.intern("shiny gold");
-        assert_eq!(126, super::part2(&contains, shiny_gold));
+        // Nine levels of 255 bags each is more than a u64 holds.
+        let deep: String = (0..10)
+            .map(|i| match i {
+                9 => format!("level {} bags contain no other bags.", i),
+                _ => format!("level {} bags contain 255 level {} bags.\n", i, i + 1),
+            })
+            .collect();
+        let graph = super::load(&deep, None).unwrap().0;
+        assert_eq!(
+            Err(GraphError::Overflow("level 0".to_string())),
+            super::part2(&graph, graph.id("level 0").unwrap())
+        );
+        assert!(super::part2(&graph, graph.id("level 1").unwrap()).is_ok());
+    }
+
+    #[test]
+    fn export_test() {
+        use super::{export, Scope};
+        let graph = super::load(EXAMPLE, None).unwrap().0;
+        let id = |name| graph.id(name).unwrap();
+        assert_eq!(
+            "digraph bags {
+    \"faded blue\";
+    \"vibrant plum\";
+    \"dotted black\";
+    \"vibrant plum\" -> \"faded blue\" [label=\"5\"];
+    \"vibrant plum\" -> \"dotted black\" [label=\"6\"];
+}
+",
+            export::to_dot(&graph, Scope::From(id("vibrant plum")))
+        );
+        assert_eq!(
+            "graph TD
+    c0[\"light red\"]
+    c1[\"bright white\"]
+    c3[\"dark orange\"]
+    c0 -->|1| c1
+    c3 -->|3| c1
+",
+            export::to_mermaid(&graph, Scope::To(id("bright white")))
+        );
+        let dot = export::to_dot(&graph, Scope::All);
+        assert_eq!(9 + 13 + 2, dot.lines().count());
+    }
+
+    #[test]
+    fn validate_test() {
+        use super::validate::{Issue, Problem};
+        use super::LoadError;
+        let broken = "light red bags contain 1 dark red bag, 2 plaid magenta bags.
+
+dark red bags contain 1 dark red bag.
+faded blue bags contain 3 plaid magenta bags.
+light red bags contain no other bags.";
+        let issue = |line, problem| Issue { line, problem };
+        assert_eq!(
+            Some(LoadError::Invalid(vec![
+                issue(1, Problem::Undefined("plaid magenta".to_string())),
+                issue(3, Problem::SelfContaining("dark red".to_string())),
+                issue(
+                    5,
+                    Problem::Duplicate {
+                        color: "light red".to_string(),
+                        first_line: 1
+                    }
+                ),
+            ])),
+            super::load(broken, None).err()
+        );
+
+        let (_, warnings) = super::load(EXAMPLE, Some("dark olive")).unwrap();
+        assert_eq!(
+            vec!["line 7: vibrant plum bags neither hold nor are held by dark olive bags"],
+            warnings
+                .iter()
+                .map(|warning| warning.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn parser_test() {
+        use super::parser::{parse_rules, ParseError, ParsedRule};
+        let rules = parse_rules(
+            "  light  red bag contain 1 bright white bag ,2 muted\tyellow bags .
+
+faded blue bags contain  no other bags.
+huge bags contain 100000 faded blue bags.",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                ParsedRule {
+                    line: 1,
+                    color: "light red".to_string(),
+                    contains: vec![
+                        (1, "bright white".to_string()),
+                        (2, "muted yellow".to_string())
+                    ],
+                },
+                ParsedRule {
+                    line: 3,
+                    color: "faded blue".to_string(),
+                    contains: vec![],
+                },
+                ParsedRule {
+                    line: 4,
+                    color: "huge".to_string(),
+                    contains: vec![(100000, "faded blue".to_string())],
+                },
+            ],
+            rules
+        );
+
+        let error = |line, column, expected| {
+            Err(ParseError {
+                line,
+                column,
+                expected,
+            })
+        };
+        assert_eq!(
+            error(2, 24, "a count"),
+            parse_rules("faded blue bags contain no other bags.\nlight red bags contain one faded blue bag.")
+        );
+        assert_eq!(
+            error(1, 16, "\"contain\""),
+            parse_rules("light red bags hold 1 faded blue bag.")
+        );
+        assert_eq!(
+            error(1, 40, "\".\""),
+            parse_rules("light red bags contain 1 faded blue bag")
+        );
+        // Columns count characters, not bytes.
+        assert_eq!(
+            error(1, 17, "\"contain\""),
+            parse_rules("café crème bags hold 1 faded blue bag.")
+        );
+        assert_eq!(
+            "line 1, column 24: expected a count",
+            parse_rules("light red bags contain 99999999999 faded blue bags.")
+                .unwrap_err()
+                .to_string()
+        );
     }
 }
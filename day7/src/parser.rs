@@ -0,0 +1,132 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    character::complete::{char, digit1, space0, space1},
+    combinator::{all_consuming, map, map_res, value, verify},
+    error::{context, VerboseError, VerboseErrorKind},
+    multi::separated_list1,
+    sequence::{delimited, preceded, terminated, tuple},
+    IResult,
+};
+use std::fmt;
+
+type ParseResult<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
+
+// A rule line with colour names normalized to single spaces between words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedRule {
+    pub line: usize,
+    pub color: String,
+    pub contains: Vec<(u32, String)>,
+}
+
+// Lines and columns are 1-based; the column is where parsing got stuck.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub expected: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}",
+            self.line, self.column, self.expected
+        )
+    }
+}
+
+fn bag(input: &str) -> ParseResult<'_, ()> {
+    context(
+        "\"bag\" or \"bags\"",
+        value((), alt((tag("bags"), tag("bag")))),
+    )(input)
+}
+
+fn word(input: &str) -> ParseResult<'_, &str> {
+    verify(
+        take_while1(|c: char| c.is_alphanumeric() || c == '-'),
+        |w: &str| w != "bag" && w != "bags",
+    )(input)
+}
+
+// One or more words up to, but not including, the following `bag(s)`.
+fn color(input: &str) -> ParseResult<'_, String> {
+    context(
+        "a color",
+        map(separated_list1(space1, word), |words| words.join(" ")),
+    )(input)
+}
+
+fn count(input: &str) -> ParseResult<'_, u32> {
+    context("a count", map_res(digit1, |n: &str| n.parse::<u32>()))(input)
+}
+
+fn item(input: &str) -> ParseResult<'_, (u32, String)> {
+    map(
+        tuple((count, space1, color, space1, bag)),
+        |(n, _, color, _, _)| (n, color),
+    )(input)
+}
+
+fn contents(input: &str) -> ParseResult<'_, Vec<(u32, String)>> {
+    alt((
+        value(
+            Vec::new(),
+            tuple((tag("no"), space1, tag("other"), space1, bag)),
+        ),
+        separated_list1(delimited(space0, char(','), space0), item),
+    ))(input)
+}
+
+fn rule(input: &str) -> ParseResult<'_, (String, Vec<(u32, String)>)> {
+    let (input, color) = preceded(space0, color)(input)?;
+    let (input, _) = tuple((space1, bag, space1))(input)?;
+    let (input, _) = context("\"contain\"", terminated(tag("contain"), space1))(input)?;
+    let (input, contents) = contents(input)?;
+    let (input, _) = context("\".\"", delimited(space0, char('.'), space0))(input)?;
+    Ok((input, (color, contents)))
+}
+
+// Parses a whole rule file, skipping blank lines. Stops at the first malformed line.
+pub fn parse_rules(text: &str) -> Result<Vec<ParsedRule>, ParseError> {
+    let mut rules = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match all_consuming(rule)(line) {
+            Ok((_, (color, contains))) => rules.push(ParsedRule {
+                line: idx + 1,
+                color,
+                contains,
+            }),
+            Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => {
+                return Err(to_parse_error(idx + 1, line, err))
+            }
+            Err(nom::Err::Incomplete(_)) => unreachable!("only complete parsers are used"),
+        }
+    }
+    Ok(rules)
+}
+
+// The first error is the innermost one and tells where parsing stopped. The first
+// context around it says what was expected there.
+fn to_parse_error(line: usize, text: &str, err: VerboseError<&str>) -> ParseError {
+    let rest = err.errors.first().map_or("", |(rest, _)| *rest);
+    let expected = err
+        .errors
+        .iter()
+        .find_map(|(_, kind)| match kind {
+            VerboseErrorKind::Context(expected) => Some(*expected),
+            _ => None,
+        })
+        .unwrap_or("end of line");
+    ParseError {
+        line,
+        column: text[..text.len() - rest.len()].chars().count() + 1,
+        expected,
+    }
+}
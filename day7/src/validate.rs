@@ -0,0 +1,141 @@
+use crate::{ColorContains, ColorId, ColorInterner};
+use prelude::InternId;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+// One rule line as parsed, before it's checked and turned into a graph.
+pub struct Rule {
+    pub line: usize,
+    pub color: ColorId,
+    pub contains: ColorContains,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    Undefined(String),
+    Duplicate { color: String, first_line: usize },
+    SelfContaining(String),
+    // Neither holds nor is held by the colour being analyzed, so it can't affect the answer.
+    Unreachable { color: String, from: String },
+}
+
+// Lines are 1-based. Undefined colours are reported on the first line that mentions them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub line: usize,
+    pub problem: Problem,
+}
+
+impl Issue {
+    // Errors reject the rule file; anything else is only a warning.
+    pub fn is_error(&self) -> bool {
+        !matches!(self.problem, Problem::Unreachable { .. })
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.problem {
+            Problem::Undefined(color) => write!(f, "{} bags are never defined", color),
+            Problem::Duplicate { color, first_line } => write!(
+                f,
+                "{} bags are already defined on line {}",
+                color, first_line
+            ),
+            Problem::SelfContaining(color) => write!(f, "{} bags contain themselves", color),
+            Problem::Unreachable { color, from } => {
+                write!(
+                    f,
+                    "{} bags neither hold nor are held by {} bags",
+                    color, from
+                )
+            }
+        }
+    }
+}
+
+// Checks every rule and reports all problems found, in line order. Unreachable colours
+// are only looked for when a `root` colour is given.
+pub fn validate(interner: &ColorInterner, rules: &[Rule], root: Option<ColorId>) -> Vec<Issue> {
+    let name = |color: ColorId| interner.resolve(color).unwrap().to_string();
+    let mut issues = Vec::new();
+    let mut defined: HashMap<ColorId, usize> = HashMap::new();
+    for rule in rules {
+        match defined.get(&rule.color) {
+            Some(&first_line) => issues.push(Issue {
+                line: rule.line,
+                problem: Problem::Duplicate {
+                    color: name(rule.color),
+                    first_line,
+                },
+            }),
+            None => {
+                defined.insert(rule.color, rule.line);
+            }
+        }
+        if rule.contains.iter().any(|&(_, child)| child == rule.color) {
+            issues.push(Issue {
+                line: rule.line,
+                problem: Problem::SelfContaining(name(rule.color)),
+            });
+        }
+    }
+
+    let mut undefined: BTreeSet<ColorId> = BTreeSet::new();
+    for rule in rules {
+        for &(_, child) in rule.contains.iter() {
+            if !defined.contains_key(&child) && undefined.insert(child) {
+                issues.push(Issue {
+                    line: rule.line,
+                    problem: Problem::Undefined(name(child)),
+                });
+            }
+        }
+    }
+
+    if let Some(root) = root {
+        let related = related_colors(interner, rules, root);
+        for rule in rules {
+            if defined[&rule.color] == rule.line && !related.contains(&rule.color) {
+                issues.push(Issue {
+                    line: rule.line,
+                    problem: Problem::Unreachable {
+                        color: name(rule.color),
+                        from: name(root),
+                    },
+                });
+            }
+        }
+    }
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+// `root` along with every colour it holds or is held by, following the rules directly
+// since they may not form a valid graph.
+fn related_colors(interner: &ColorInterner, rules: &[Rule], root: ColorId) -> BTreeSet<ColorId> {
+    let mut children: Vec<Vec<ColorId>> = vec![Vec::new(); interner.len()];
+    let mut parents: Vec<Vec<ColorId>> = vec![Vec::new(); interner.len()];
+    for rule in rules {
+        for &(_, child) in rule.contains.iter() {
+            children[rule.color.index()].push(child);
+            parents[child.index()].push(rule.color);
+        }
+    }
+    let mut related = BTreeSet::new();
+    related.insert(root);
+    for edges in [&children, &parents].iter() {
+        let mut seen = BTreeSet::new();
+        let mut stack = vec![root];
+        while let Some(color) = stack.pop() {
+            for &next in edges[color.index()].iter() {
+                if seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        related.extend(seen);
+    }
+    related
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+// Implemented by the newtype IDs an `Interner` hands out, so IDs from different
+// interners can't be mixed up.
+pub trait InternId: Copy + Eq + Hash {
+    fn from_index(index: usize) -> Self;
+    fn index(self) -> usize;
+}
+
+// Each distinct string is allocated once and shared between the ID lookup and the
+// string lookup. IDs are handed out densely from 0 in the order strings are first seen.
+pub struct Interner<Id> {
+    strings: Vec<Rc<str>>,
+    ids: HashMap<Rc<str>, Id>,
+    bytes: usize,
+    _id: PhantomData<Id>,
+}
+
+impl<Id: InternId> Interner<Id> {
+    pub fn new() -> Self {
+        Self {
+            strings: Vec::new(),
+            ids: HashMap::new(),
+            bytes: 0,
+            _id: PhantomData,
+        }
+    }
+
+    pub fn intern(&mut self, s: &str) -> Id {
+        if let Some(&id) = self.ids.get(s) {
+            return id;
+        }
+        let id = Id::from_index(self.strings.len());
+        let text: Rc<str> = Rc::from(s);
+        self.bytes += text.len();
+        self.strings.push(Rc::clone(&text));
+        self.ids.insert(text, id);
+        id
+    }
+
+    // The ID of an already interned string.
+    pub fn get(&self, s: &str) -> Option<Id> {
+        self.ids.get(s).copied()
+    }
+
+    pub fn resolve(&self, id: Id) -> Option<&str> {
+        self.strings.get(id.index()).map(|s| &**s)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &str)> + '_ {
+        self.strings
+            .iter()
+            .enumerate()
+            .map(|(idx, s)| (Id::from_index(idx), &**s))
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    // Total length of the interned strings, not counting bookkeeping.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+impl<Id: InternId> Default for Interner<Id> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InternId, Interner};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    struct Name(u32);
+
+    impl InternId for Name {
+        fn from_index(index: usize) -> Self {
+            Name(index as u32)
+        }
+
+        fn index(self) -> usize {
+            self.0 as usize
+        }
+    }
+
+    #[test]
+    fn interning() {
+        let mut names: Interner<Name> = Interner::new();
+        assert!(names.is_empty());
+
+        let red = names.intern("light red");
+        let gold = names.intern("shiny gold");
+        assert_eq!(red, names.intern("light red"));
+        let blue = names.intern("faded blue");
+        assert_eq!(gold, names.intern("shiny gold"));
+        assert_eq!((Name(0), Name(1), Name(2)), (red, gold, blue));
+
+        assert_eq!(3, names.len());
+        assert!(!names.is_empty());
+        assert_eq!(
+            "light red".len() + "shiny gold".len() + "faded blue".len(),
+            names.bytes()
+        );
+
+        assert_eq!(Some(gold), names.get("shiny gold"));
+        assert_eq!(None, names.get("dark olive"));
+        assert_eq!(3, names.len());
+        assert_eq!(Some("faded blue"), names.resolve(blue));
+        assert_eq!(None, names.resolve(Name(3)));
+        assert_eq!(
+            vec![
+                (red, "light red"),
+                (gold, "shiny gold"),
+                (blue, "faded blue")
+            ],
+            names.iter().collect::<Vec<_>>()
+        );
+    }
+}
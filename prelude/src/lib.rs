@@ -1,5 +1,47 @@
 use std::fs;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+mod interner;
+
+pub use interner::{InternId, Interner};
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+// Quiet by default; `-v`/`--verbose` on the command line turns on `verbose!` output.
+pub fn init_verbosity() {
+    set_verbose(
+        std::env::args()
+            .skip(1)
+            .any(|a| a == "-v" || a == "--verbose"),
+    );
+}
+
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+#[macro_export]
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if $crate::is_verbose() {
+            println!($($arg)*);
+        }
+    };
+}
+
+// The value following `flag` in `args`, if the flag is given at all.
+pub fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == flag).map(|idx| {
+        args.get(idx + 1)
+            .unwrap_or_else(|| panic!("{} requires a value", flag))
+            .as_str()
+    })
+}
 
 pub fn read_input<P: AsRef<Path>>(path: P) -> String {
     let text: String = match fs::read_to_string(&path) {
@@ -21,7 +63,7 @@ pub fn read_input<P: AsRef<Path>>(path: P) -> String {
 pub fn read_input_lines<P: AsRef<Path>>(path: P) -> Vec<String> {
     read_input(path)
         .split("\n")
-        .filter(|x| x.len() > 0)
+        .filter(|x| !x.is_empty())
         .map(|x| x.replace("\r", ""))
         .collect()
 }